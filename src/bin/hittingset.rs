@@ -1,6 +1,8 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 
+use chext::file_handling::PathReader;
 use chext::graph::NodeId;
 use chext::graph::{BaseNode, CHEdge, CHGraph};
 use chext::hittingset::HittingSet;
@@ -144,12 +146,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         let check = {
             let g = load_ch_graph(&args.graph_file)?;
-            let mut paths = Vec::new();
+            let mut ok = true;
             for file in &args.paths_files {
-                let mut p = load_paths(file)?;
-                paths.append(&mut p);
+                let reader = PathReader::new(file)?;
+                let nodes: Vec<NodeId> = hittingset.iter().map(|&(node, _)| node).collect();
+                if !check_hitting_set_stream(&nodes, reader, &g)? {
+                    ok = false;
+                    break;
+                }
             }
-            check_hitting_set_par(&hittingset, &paths, &g)
+            ok
         };
         assert!(check, "hittingset not correct");
     }
@@ -165,32 +171,37 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// checks the hitting set by expanding each path and checking if one of the nodes is in the hitting set
-/// parallel with rayon
-fn check_hitting_set_par<N: BaseNode, E: CHEdge>(
-    hittingset: &Vec<NodeId>,
-    paths: &Vec<CHEdgeList>,
+/// streaming counterpart to the old `Vec`-based checker: consumes paths lazily from a
+/// `PathReader` (or any other `CHEdgeList` result iterator) in bounded-size chunks, testing each
+/// chunk in a rayon-parallel pass against a `HashSet<NodeId>` built once from the hitting set,
+/// instead of ever materializing the whole paths corpus. lets `-p` verification run over path
+/// collections far larger than RAM.
+fn check_hitting_set_stream<N: BaseNode, E: CHEdge>(
+    hittingset: &[NodeId],
+    mut paths: impl Iterator<Item = Result<CHEdgeList, csv::Error>>,
     graph: &dyn CHGraph<Node = N, Edge = E>,
-) -> bool {
-    paths
-        .par_iter()
-        .map(|path| {
-            // map a path to true if there is a node in the hittingset that hits this path, otherwise map to false
-            // unpack ch path to full path
+) -> Result<bool, csv::Error> {
+    const CHUNK_SIZE: usize = 10_000;
+    let hitting_set: HashSet<NodeId> = hittingset.iter().copied().collect();
+
+    loop {
+        let chunk: Vec<CHEdgeList> = paths.by_ref().take(CHUNK_SIZE).collect::<Result<_, _>>()?;
+        if chunk.is_empty() {
+            return Ok(true);
+        }
+
+        let all_hit = chunk.par_iter().all(|path| {
+            // unpack ch path to full path, then turn edge-path into node-path
             let full_path = graph.unpack_ch_edges(path);
-            // turn edge-path into node-path
             let mut node_path = Vec::with_capacity(full_path.0.len() + 1);
             node_path.push(graph.edge(full_path.0[0]).source());
             for e in full_path {
                 node_path.push(graph.edge(e).target());
             }
-            // check if a node of the hittingset is on the path
-            for node in hittingset {
-                if node_path.contains(node) {
-                    return true;
-                }
-            }
-            return false;
-        })
-        .all(|t| t) // returns true if all map results are true => all paths are hit by the set
+            node_path.iter().any(|node| hitting_set.contains(node))
+        });
+        if !all_hit {
+            return Ok(false);
+        }
+    }
 }
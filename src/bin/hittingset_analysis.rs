@@ -1,6 +1,7 @@
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 
+use garp::cache;
 use garp::graph::NodeId;
 use garp::graph::{BaseNode, CHEdge, CHGraph};
 use garp::hittingset::HittingSet;
@@ -34,6 +35,9 @@ struct Args<'a> {
 
     /// limits the maximum iterations that the hitting set algorithm will run for
     maxiter: Option<usize>,
+
+    /// resume from a hitting set file written by a previous (possibly maxiter-capped) run
+    resume: Option<&'a str>,
 }
 
 /// takes a graph and one or more paths files and generates a hitting set
@@ -95,6 +99,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .help("limits the maximum iterations that the hitting set algorithm will run for")
             .takes_value(true),
     )
+    .arg(
+        Arg::with_name("resume")
+            .long("resume")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("resume from a hitting set file written by a previous run, instead of starting from scratch"),
+    )
     .get_matches();
 
     let args = Args {
@@ -110,8 +121,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         skip_verification: matches.is_present("skip verification"),
         verbose: matches.is_present("verbose"),
         maxiter: matches.value_of("maxiter").and_then(|d| d.parse().ok()),
+        resume: matches.value_of("resume"),
     };
 
+    // skip the whole computation if a previous run with the same path files and parameters
+    // already produced this exact output
+    let mut paths_bytes = Vec::new();
+    for file in &args.paths_files {
+        paths_bytes.extend(std::fs::read(file)?);
+    }
+    let cache_params = format!("{:?}|{:?}|{:?}", args.maxiter, args.resume, args.lower_bound);
+    let cache_key = cache::content_key(&[&paths_bytes, cache_params.as_bytes()]);
+    if cache::is_current(args.out_file, &cache_key) {
+        if args.verbose {
+            println!("{} is already up to date, skipping computation", args.out_file);
+        }
+        return Ok(());
+    }
+
     // load paths
     let paths = {
         if args.verbose {
@@ -148,7 +175,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("loading graph");
         }
         let hsgraph = load_hs_graph(&args.graph_file)?;
-        let hs_calc = HittingSet::new(&hsgraph, paths);
+        let resume = match args.resume {
+            Some(file) => {
+                if args.verbose {
+                    println!("loading resume file");
+                }
+                load_resume(file)?
+            }
+            None => Vec::new(),
+        };
+        let hs_calc = HittingSet::new_with_resume(&hsgraph, paths, 400000, 4, &|_| true, resume);
 
         // calc lower bound
         let lower_bound = if args.lower_bound {
@@ -212,10 +248,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         buf.write(format!("{}, {}\n", String::from(h.0), h.1).as_bytes())
             .unwrap();
     }
+    drop(buf);
+
+    cache::store_key(args.out_file, &cache_key)?;
 
     Ok(())
 }
 
+/// loads a hitting set file in the format `main` writes above (`"NodeId, weight"` header followed by
+/// one `"id, weight"` line per hitter), for `--resume`
+fn load_resume(filename: &str) -> Result<Vec<(NodeId, u64)>, Box<dyn std::error::Error>> {
+    let file = File::open(filename)?;
+    let reader = BufReader::new(file);
+    let mut resume = Vec::new();
+    for line in reader.lines().skip(1) {
+        let line = line?;
+        let (node, weight) = line
+            .split_once(", ")
+            .ok_or_else(|| format!("malformed resume file line: {}", line))?;
+        resume.push((node.trim().parse::<usize>()?.into(), weight.trim().parse::<u64>()?));
+    }
+    Ok(resume)
+}
+
 /// checks the hitting set by expanding each path and checking if one of the nodes is in the hitting set
 /// parallel with rayon
 fn check_hitting_set_par<N: BaseNode, E: CHEdge>(
@@ -1,11 +1,12 @@
-use garp::graph::{BaseNode, CHEdge, CHGraph};
+use garp::graph::{BaseNode, CHEdge, CHGraph, GeoNode};
 use clap::{App, Arg};
 use std::io::{stdout, Write};
 
+use garp::cache;
 use garp::dijkstra::Dijkstra;
 use garp::paths::SourceTargetPair;
-use garp::random_pairs::STPGenerator;
-use garp::{load_ch_graph, PathWriter};
+use garp::random_pairs::{STPGenerator, SpatialSTPGenerator, WaypointGenerator};
+use garp::{load_ch_graph, PathFormat, PathWriter};
 use rayon::iter::{ParallelBridge, ParallelIterator};
 
 // struct to store command line args.
@@ -17,6 +18,9 @@ struct Args<'a> {
     out_file: &'a str,
     parallel: bool,
     verbose: bool,
+    binary: bool,
+    k: usize,
+    dist_range: Option<(f64, f64)>,
 }
 
 /// this binary generates random point pairs and calculates the shortest path.
@@ -59,6 +63,35 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("run calculation multithreaded"),
         )
         .arg(Arg::with_name("verbose").short("v").help("output progress"))
+        .arg(
+            Arg::with_name("binary format")
+                .short("b")
+                .help("store paths in the binary format instead of text"),
+        )
+        .arg(
+            Arg::with_name("waypoints")
+                .short("k")
+                .value_name("k")
+                .takes_value(true)
+                .default_value("2")
+                .help("number of waypoints per path; k=2 is a plain source/target pair, k>2 visits the waypoints in whatever order minimizes total path cost"),
+        )
+        .arg(
+            Arg::with_name("dist-min")
+                .long("dist-min")
+                .value_name("METERS")
+                .takes_value(true)
+                .requires("dist-max")
+                .help("sample source/target pairs (k=2 only) with a great-circle distance of at least this many meters, instead of uniformly"),
+        )
+        .arg(
+            Arg::with_name("dist-max")
+                .long("dist-max")
+                .value_name("METERS")
+                .takes_value(true)
+                .requires("dist-min")
+                .help("sample source/target pairs (k=2 only) with a great-circle distance of at most this many meters, instead of uniformly"),
+        )
         .get_matches();
 
     // store cli args into Args struct
@@ -76,8 +109,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         out_file: matches.value_of("out file").unwrap(),
         parallel: matches.is_present("parallel processing"),
         verbose: matches.is_present("verbose"),
+        binary: matches.is_present("binary format"),
+        k: matches
+            .value_of("waypoints")
+            .unwrap()
+            .parse::<usize>()
+            .unwrap(),
+        dist_range: matches.value_of("dist-min").map(|dist_min| {
+            let dist_min = dist_min.parse::<f64>().unwrap();
+            let dist_max = matches
+                .value_of("dist-max")
+                .unwrap()
+                .parse::<f64>()
+                .unwrap();
+            (dist_min, dist_max)
+        }),
     };
 
+    // skip generation entirely if a previous run already produced this exact output: same graph
+    // file content and same generation parameters
+    let graph_bytes = std::fs::read(args.graph_file)?;
+    let cache_params = format!(
+        "{:?}|{:?}|{:?}|{:?}|{:?}",
+        args.n, args.seed, args.k, args.dist_range, args.binary
+    );
+    let cache_key = cache::content_key(&[&graph_bytes, cache_params.as_bytes()]);
+    if cache::is_current(args.out_file, &cache_key) {
+        if args.verbose {
+            println!("{} is already up to date, skipping generation", args.out_file);
+        }
+        return Ok(());
+    }
+
     // load the graph
     if args.verbose {
         println!("Loading graph");
@@ -87,6 +150,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("done");
     }
 
+    let format = if args.binary {
+        PathFormat::Binary
+    } else {
+        PathFormat::Text
+    };
+
     // run path generation
     if args.parallel {
         generate_and_store_par(
@@ -95,20 +164,37 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             &g,
             args.out_file.to_string(),
             args.verbose,
+            format,
+            args.k,
+            args.dist_range,
         );
     } else {
-        generate_and_store(args.n, args.seed, &g, &args.out_file, args.verbose);
+        generate_and_store(
+            args.n,
+            args.seed,
+            &g,
+            &args.out_file,
+            args.verbose,
+            format,
+            args.k,
+            args.dist_range,
+        );
     }
 
+    cache::store_key(args.out_file, &cache_key)?;
+
     Ok(())
 }
 
-fn generate_and_store_par<N: BaseNode, E: CHEdge>(
+fn generate_and_store_par<N: BaseNode + GeoNode, E: CHEdge>(
     n: usize,
     seed: Option<u64>,
     graph: &dyn CHGraph<Node = N, Edge = E>,
     filename: String,
     verbose: bool,
+    format: PathFormat,
+    k: usize,
+    dist_range: Option<(f64, f64)>,
 ) {
     // create channel to send all results to a writer thread
     let (send, recv) = std::sync::mpsc::sync_channel(rayon::current_num_threads());
@@ -119,7 +205,7 @@ fn generate_and_store_par<N: BaseNode, E: CHEdge>(
         if verbose {
             print!("paths generated: {}", i);
         }
-        let mut wtr = PathWriter::new(&filename, false);
+        let mut wtr = PathWriter::new(&filename, false, format);
         for path in recv {
             wtr.save_path(path);
             i += 1;
@@ -131,26 +217,59 @@ fn generate_and_store_par<N: BaseNode, E: CHEdge>(
         println!();
     });
 
-    // source target pair generator is an iterator - allows easy parallel processing with rayon.
-    let generator = STPGenerator::new(graph.num_nodes(), seed, n);
-
     if verbose {
         println!("starting path generation");
     }
 
-    generator.into_iter().par_bridge().for_each_init(
-        || Dijkstra::new(graph),
-        |dijkstra, SourceTargetPair { source, target }| {
-            if source == target {
-                return;
-            } // skip cases where there is no real path
-            let path = dijkstra.ch_search(source, target);
-            if let Some((_, path)) = path {
-                // do not store pairs that are not connected
-                send.send(path).expect("error sending data");
-            }
-        },
-    );
+    if k == 2 {
+        if let Some((dist_min, dist_max)) = dist_range {
+            let generator = SpatialSTPGenerator::new(graph, seed, n, dist_min, dist_max);
+
+            generator.into_iter().par_bridge().for_each_init(
+                || Dijkstra::new(graph),
+                |dijkstra, pair| {
+                    let Some(SourceTargetPair { source, target }) = pair else {
+                        return; // no node fell within the requested distance band
+                    };
+                    let path = dijkstra.ch_search(source, target);
+                    if let Some((_, path)) = path {
+                        // do not store pairs that are not connected
+                        send.send(path).expect("error sending data");
+                    }
+                },
+            );
+        } else {
+            // source target pair generator is an iterator - allows easy parallel processing with rayon.
+            let generator = STPGenerator::new(graph.num_nodes(), seed, n);
+
+            generator.into_iter().par_bridge().for_each_init(
+                || Dijkstra::new(graph),
+                |dijkstra, SourceTargetPair { source, target }| {
+                    if source == target {
+                        return;
+                    } // skip cases where there is no real path
+                    let path = dijkstra.ch_search(source, target);
+                    if let Some((_, path)) = path {
+                        // do not store pairs that are not connected
+                        send.send(path).expect("error sending data");
+                    }
+                },
+            );
+        }
+    } else {
+        let generator = WaypointGenerator::new(graph.num_nodes(), seed, n, k);
+
+        generator.into_iter().par_bridge().for_each_init(
+            || Dijkstra::new(graph),
+            |dijkstra, waypoints| {
+                let path = dijkstra.waypoint_search(&waypoints, false);
+                if let Some((_, path)) = path {
+                    // do not store tuples with a disconnected leg
+                    send.send(path).expect("error sending data");
+                }
+            },
+        );
+    }
 
     drop(send); // ! without this line the receiver will never stop waiting for more data
 
@@ -161,17 +280,18 @@ fn generate_and_store_par<N: BaseNode, E: CHEdge>(
     }
 }
 
-fn generate_and_store<N: BaseNode, E: CHEdge>(
+fn generate_and_store<N: BaseNode + GeoNode, E: CHEdge>(
     n: usize,
     seed: Option<u64>,
     graph: &dyn CHGraph<Node = N, Edge = E>,
     filename: &str,
     verbose: bool,
+    format: PathFormat,
+    k: usize,
+    dist_range: Option<(f64, f64)>,
 ) {
     let mut dijkstra = Dijkstra::new(graph);
 
-    let generator = STPGenerator::new(graph.num_nodes(), seed, n);
-
     let mut i = 0;
     if verbose {
         println!("starting path generation");
@@ -179,19 +299,55 @@ fn generate_and_store<N: BaseNode, E: CHEdge>(
     if verbose {
         print!("paths generated: {}", i);
     }
-    let mut wtr = PathWriter::new(&filename, false);
-    for SourceTargetPair { source, target } in generator {
-        if source == target {
-            continue;
-        }
-        let path = dijkstra.ch_search(source, target);
-        if let Some((_, path)) = path {
-            wtr.save_path(path);
+    let mut wtr = PathWriter::new(&filename, false, format);
+
+    if k == 2 {
+        if let Some((dist_min, dist_max)) = dist_range {
+            let generator = SpatialSTPGenerator::new(graph, seed, n, dist_min, dist_max);
+            for pair in generator {
+                let Some(SourceTargetPair { source, target }) = pair else {
+                    i += 1;
+                    continue; // no node fell within the requested distance band
+                };
+                let path = dijkstra.ch_search(source, target);
+                if let Some((_, path)) = path {
+                    wtr.save_path(path);
+                }
+                i += 1;
+                if i % 1000 == 0 && verbose {
+                    print!("\rpaths generated: {}", i);
+                    stdout().flush().unwrap();
+                }
+            }
+        } else {
+            let generator = STPGenerator::new(graph.num_nodes(), seed, n);
+            for SourceTargetPair { source, target } in generator {
+                if source == target {
+                    continue;
+                }
+                let path = dijkstra.ch_search(source, target);
+                if let Some((_, path)) = path {
+                    wtr.save_path(path);
+                }
+                i += 1;
+                if i % 1000 == 0 && verbose {
+                    print!("\rpaths generated: {}", i);
+                    stdout().flush().unwrap();
+                }
+            }
         }
-        i += 1;
-        if i % 1000 == 0 && verbose {
-            print!("\rpaths generated: {}", i);
-            stdout().flush().unwrap();
+    } else {
+        let generator = WaypointGenerator::new(graph.num_nodes(), seed, n, k);
+        for waypoints in generator {
+            let path = dijkstra.waypoint_search(&waypoints, false);
+            if let Some((_, path)) = path {
+                wtr.save_path(path);
+            }
+            i += 1;
+            if i % 1000 == 0 && verbose {
+                print!("\rpaths generated: {}", i);
+                stdout().flush().unwrap();
+            }
         }
     }
 }
@@ -2,7 +2,7 @@ use std::time::Instant;
 
 use garp::{
     graph::BaseGraph,
-    load_ch_graph,
+    load_ch_graph, load_fmi_graph_mmap,
     quadtree::{QuadTree, TreeNode},
     wspd::Tree,
 };
@@ -17,6 +17,9 @@ struct Args<'a> {
 
     /// max tree depth
     maxdepth: usize,
+
+    /// open the graph as a zero-copy mmap instead of loading it fully into memory
+    mmap: bool,
 }
 
 /// calculates some information on quadtrees of different depths
@@ -38,6 +41,9 @@ fn main() {
         .takes_value(true)
         .required(true)
         .index(3))
+    .arg(Arg::with_name("mmap")
+        .long("mmap")
+        .help("open the graph as a zero-copy mmap instead of loading it fully into memory"))
     .get_matches();
 
     let args = Args {
@@ -50,10 +56,23 @@ fn main() {
             .value_of("min tree depth")
             .and_then(|d| d.parse().ok())
             .expect("no valid min depth value provided"),
+        mmap: matches.is_present("mmap"),
     };
 
-    let graph = load_ch_graph(args.graph_file).unwrap();
+    if args.mmap {
+        let graph = load_fmi_graph_mmap(args.graph_file).unwrap();
+        run_analysis(&graph, args.mindepth, args.maxdepth);
+    } else {
+        let graph = load_ch_graph(args.graph_file).unwrap();
+        run_analysis(&graph, args.mindepth, args.maxdepth);
+    }
+}
 
+/// builds quadtrees of increasing depth over `graph`'s nodes and prints size/shape stats for each
+fn run_analysis<G: BaseGraph>(graph: &G, mindepth: usize, maxdepth: usize)
+where
+    G::Node: TreeNode,
+{
     let tree_data: Vec<&dyn TreeNode> = graph
         .iter_nodes()
         .map(|n| {
@@ -63,7 +82,7 @@ fn main() {
         .collect();
 
     println!("maxdepth, duration, #leafs, #nodes, mean leaf size, leafs with one point");
-    for k in args.mindepth..args.maxdepth + 1 {
+    for k in mindepth..maxdepth + 1 {
         let now = Instant::now();
         let quadtree = QuadTree::new(tree_data.clone(), k);
         let duration = now.elapsed();
@@ -5,11 +5,15 @@ use garp::{
     graph::{BaseGraph, CHGraph},
     load_ch_graph, load_hittingset, load_paths,
     quadtree::{QuadTree, TreeNode},
-    vis::{Color, GeoJsonBuilder, MapBuilder, VisBuilder},
+    vis::{Color, DotBuilder, GeoJsonBuilder, GeoZeroBuilder, MapBuilder, PolylineBuilder, VisBuilder},
     wspd::{Tree, WSPD},
 };
 use clap::{App, Arg};
 
+/// geozero-supported output extensions; `.polyline` and `.dot` have their own builders, and any
+/// other extension falls back to the plain GeoJsonBuilder
+const GEOZERO_EXTENSIONS: &[&str] = &["svg", "wkt", "csv", "fgb", "flatgeobuf"];
+
 // #[derive(Parser)]
 // #[clap(author, version, about, long_about = None)]
 // #[clap(subcommandsRequired)]
@@ -144,12 +148,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut dijkstra = Dijkstra::new(&graph);
 
     let mut map_builder = MapBuilder::germany(&graph)?;
-    let mut geo_builder = GeoJsonBuilder::new(&graph);
+    let mut geojson_builder = GeoJsonBuilder::new(&graph);
+    let mut geozero_builder = GeoZeroBuilder::new(&graph);
+    let mut polyline_builder = PolylineBuilder::new(&graph);
+    let mut dot_builder = DotBuilder::new(&graph);
+
+    let out_ext = std::path::Path::new(args.out_file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
 
     let builder: &mut dyn VisBuilder = if args.image {
         &mut map_builder
+    } else if out_ext == "polyline" {
+        &mut polyline_builder
+    } else if out_ext == "dot" {
+        &mut dot_builder
+    } else if GEOZERO_EXTENSIONS.contains(&out_ext.as_str()) {
+        &mut geozero_builder
     } else {
-        &mut geo_builder
+        &mut geojson_builder
     };
 
     if let Some(paths_file) = args.paths_file {
@@ -1,12 +1,19 @@
+use std::hash::Hash;
 use std::io::{stdout, Write};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::time::Duration;
 
 use garp::{
     dijkstra::Dijkstra,
     graph::{BaseGraph, BaseNode, CHEdge, CHGraph},
     load_ch_graph,
-    quadtree::{QuadTree, TreeNode},
-    wspd::WSPD,
-    PathWriter,
+    paths::CHEdgeList,
+    quadtree::{PointContainer, QuadTree, TreeNode},
+    rstartree::RStarTree,
+    rtree::RTree,
+    wspd::{Distance, ProgressGate, Tree, WspdProgress, WSPD},
+    BatchedPathWriter,
 };
 use clap::{App, Arg};
 use rayon::iter::{ParallelBridge, ParallelIterator};
@@ -18,12 +25,19 @@ struct Args<'a> {
     /// file to store the paths
     out_file: &'a str,
 
-    /// max tree depth. defaults to usize::MAX
+    /// max tree depth. defaults to usize::MAX. only used for the quadtree index
     maxdepth: usize,
 
     /// epsilon parameter for the WSPD
     epsilon: f64,
 
+    /// spatial index to build the WSPD over: "quadtree" (default), "rtree", or "rstar"
+    index: &'a str,
+
+    /// milliseconds between progress reports during WSPD decomposition and path generation.
+    /// omit to disable progress reporting.
+    progress_interval: Option<u64>,
+
     verbose: bool,
 }
 
@@ -49,6 +63,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .help("epsilon for wspd calculation")
             .default_value("0.5")
             .takes_value(true))
+        .arg(Arg::with_name("index")
+            .long("index")
+            .help("spatial index to build the WSPD over")
+            .possible_values(&["quadtree", "rtree", "rstar"])
+            .default_value("quadtree")
+            .takes_value(true))
+        .arg(Arg::with_name("progress interval")
+            .long("progress-interval")
+            .help("milliseconds between progress reports; omit to disable")
+            .takes_value(true))
         .arg(Arg::with_name("verbose")
             .short("v")
             .long("verbose")
@@ -67,6 +91,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap()
             .parse()
             .expect("no valid epsilon provided"),
+        index: matches.value_of("index").unwrap(),
+        progress_interval: matches
+            .value_of("progress interval")
+            .map(|i| i.parse().expect("no valid progress interval provided")),
         verbose: matches.is_present("verbose"),
     };
 
@@ -74,37 +102,95 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let graph = load_ch_graph(args.graph_file)?;
 
-    // 1. generate quad tree
-    let tree_data = graph
+    let tree_data: Vec<&dyn TreeNode> = graph
         .iter_nodes()
         .map(|n| {
             let tn: &dyn TreeNode = n;
             tn
         })
         .collect();
-    let quadtree = QuadTree::new(tree_data, args.maxdepth);
 
-    // 2. calculate wspd
-    let wspd = WSPD::new(&quadtree, args.epsilon);
+    let progress_interval = args.progress_interval.map(Duration::from_millis);
 
-    if args.verbose {
-        println!("wspd done. size: {}", wspd.len());
-    }
+    if args.index == "rtree" {
+        // 1. build the R*-tree
+        let rtree = RTree::new(tree_data);
+
+        // 2. calculate wspd
+        let wspd = build_wspd(&rtree, args.epsilon, progress_interval);
+        if args.verbose {
+            println!("wspd done. size: {}", wspd.len());
+        }
+
+        // 3. iterate pairs, pick a repr point from both sets and find a shortest path. store to file.
+        sample_path_and_store_par(&graph, &wspd, args.out_file.to_string(), args.verbose, progress_interval);
+    } else if args.index == "rstar" {
+        // 1. build the hand-rolled STR-packed R*-tree
+        let rstartree = RStarTree::new(tree_data);
+
+        // 2. calculate wspd
+        let wspd = build_wspd(&rstartree, args.epsilon, progress_interval);
+        if args.verbose {
+            println!("wspd done. size: {}", wspd.len());
+        }
+
+        // 3. iterate pairs, pick a repr point from both sets and find a shortest path. store to file.
+        sample_path_and_store_par(&graph, &wspd, args.out_file.to_string(), args.verbose, progress_interval);
+    } else {
+        // 1. generate quad tree
+        let quadtree = QuadTree::new(tree_data, args.maxdepth);
 
-    // 3. iterate pairs, pick a repr point from both sets and find a shortest path. store to file.
-    sample_path_and_store_par(&graph, &wspd, args.out_file.to_string(), args.verbose);
+        // 2. calculate wspd
+        let wspd = build_wspd(&quadtree, args.epsilon, progress_interval);
+        if args.verbose {
+            println!("wspd done. size: {}", wspd.len());
+        }
+
+        // 3. iterate pairs, pick a repr point from both sets and find a shortest path. store to file.
+        sample_path_and_store_par(&graph, &wspd, args.out_file.to_string(), args.verbose, progress_interval);
+    }
 
     Ok(())
 }
 
-fn sample_path_and_store_par<N: BaseNode, E: CHEdge>(
+/// builds a WSPD over `tree`, reporting progress every `progress_interval` if set. progress
+/// reports are print-only here; embedders that need to abort should call
+/// `WSPD::new_with_progress` directly with their own `ControlFlow::Break`-returning callback.
+fn build_wspd<'a, T>(tree: &'a T, epsilon: f64, progress_interval: Option<Duration>) -> WSPD<'a, T>
+where
+    T: Tree<'a> + Distance + Eq + Hash + Sync,
+{
+    match progress_interval {
+        Some(interval) => WSPD::new_with_progress(tree, epsilon, interval, |p: &WspdProgress| {
+            println!(
+                "wspd: {} pairs decomposed ({:.0}/s), depth {}, {:?} elapsed",
+                p.pairs_decomposed,
+                p.pairs_per_sec(),
+                p.depth,
+                p.elapsed
+            );
+            ControlFlow::Continue(())
+        }),
+        None => WSPD::new(tree, epsilon),
+    }
+}
+
+fn sample_path_and_store_par<'a, N: BaseNode, E: CHEdge, T>(
     graph: &dyn CHGraph<Node = N, Edge = E>,
-    wspd: &WSPD<QuadTree>,
+    wspd: &'a WSPD<'a, T>,
     filename: String,
     verbose: bool,
-) {
-    // create channel to send all results to a writer thread
-    let (send, recv) = std::sync::mpsc::sync_channel(rayon::current_num_threads());
+    progress_interval: Option<Duration>,
+) where
+    T: Tree<'a> + Distance + Eq + Hash + Sync + PointContainer<'a>,
+{
+    // the writer dictates the preferred batch size, so each worker's thread-local buffer
+    // can be sized to flush in exactly one serialization call per batch.
+    let wtr = BatchedPathWriter::new(&filename, false, 1000);
+    let batch_size = wtr.batch_size();
+
+    // create channel to send batches of results to a writer thread
+    let (send, recv) = std::sync::mpsc::sync_channel::<Vec<CHEdgeList>>(rayon::current_num_threads());
 
     // Spawn a thread that is dedicated to writing results
     let writer_thread = std::thread::spawn(move || {
@@ -112,11 +198,11 @@ fn sample_path_and_store_par<N: BaseNode, E: CHEdge>(
         if verbose {
             print!("paths generated: {}", i);
         }
-        let mut wtr = PathWriter::new(&filename, false);
-        for path in recv {
-            wtr.save_path(path);
-            i += 1;
-            if i % 10000 == 0 && verbose {
+        let mut wtr = wtr;
+        for batch in recv {
+            i += batch.len();
+            wtr.save_batch(&batch);
+            if verbose {
                 print!("\rpaths generated: {}", i);
                 stdout().flush().unwrap();
             }
@@ -128,16 +214,41 @@ fn sample_path_and_store_par<N: BaseNode, E: CHEdge>(
         println!("starting path generation");
     }
 
-    // iterate the wspd and calculate a path for each pair.
+    let total_pairs = wspd.len();
+    let paths_emitted = AtomicUsize::new(0);
+    let gate = progress_interval.map(ProgressGate::new);
+
+    // iterate the wspd and calculate a path for each pair. each rayon worker buffers up to
+    // `batch_size` paths thread-locally and sends full batches, flushing whatever is left
+    // through `PathBuffer`'s Drop impl once its share of the iteration completes.
     wspd.iter().par_bridge().for_each_init(
-        || Dijkstra::new(graph),
-        |dijkstra, (u, v)| {
+        || (Dijkstra::new(graph), PathBuffer::new(send.clone(), batch_size)),
+        |(dijkstra, buffer), (u, v)| {
             let u_nodes = u.points().map(|p| p.id());
             let v_nodes = v.points().map(|p| p.id());
 
             if let Some((_dist, mut path)) = dijkstra.ch_search_multi(u_nodes, v_nodes) {
                 path.weight = (u.size() * v.size()) as u64; // set weight to #point pairs in the wspd pair
-                send.send(path).expect("error sending data");
+                buffer.push(path);
+
+                let emitted = paths_emitted.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+                if let Some(gate) = &gate {
+                    if gate.ready() {
+                        let progress = WspdProgress {
+                            pairs_decomposed: total_pairs,
+                            paths_emitted: emitted,
+                            depth: 0,
+                            elapsed: gate.elapsed(),
+                        };
+                        println!(
+                            "paths: {}/{} emitted ({:.0}/s), {:?} elapsed",
+                            progress.paths_emitted,
+                            total_pairs,
+                            progress.paths_per_sec(),
+                            progress.elapsed
+                        );
+                    }
+                }
             }
         },
     );
@@ -148,3 +259,41 @@ fn sample_path_and_store_par<N: BaseNode, E: CHEdge>(
         eprintln!("Unable to join internal thread: {:?}", e);
     }
 }
+
+/// per-worker buffer that batches paths before sending them over the channel, flushing
+/// whatever is left when the buffer (and with it, the rayon task) is dropped
+struct PathBuffer {
+    buf: Vec<CHEdgeList>,
+    batch_size: usize,
+    send: std::sync::mpsc::SyncSender<Vec<CHEdgeList>>,
+}
+
+impl PathBuffer {
+    fn new(send: std::sync::mpsc::SyncSender<Vec<CHEdgeList>>, batch_size: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(batch_size),
+            batch_size,
+            send,
+        }
+    }
+
+    fn push(&mut self, path: CHEdgeList) {
+        self.buf.push(path);
+        if self.buf.len() >= self.batch_size {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.buf.is_empty() {
+            let batch = std::mem::replace(&mut self.buf, Vec::with_capacity(self.batch_size));
+            self.send.send(batch).expect("error sending data");
+        }
+    }
+}
+
+impl Drop for PathBuffer {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
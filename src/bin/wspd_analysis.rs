@@ -2,7 +2,8 @@ use std::{iter::repeat, time::Instant};
 
 use chext::{
     dijkstra::Dijkstra,
-    graph::{BaseGraph, BaseNode, CHEdge, CHGraph, NodeId},
+    distance_oracle::DistanceOracle,
+    graph::{BaseGraph, BaseNode, CHEdge, CHGraph, NodeId, StoreableGraph},
     load_ch_graph,
     quadtree::{QuadTree, TreeNode},
     wspd::WSPD,
@@ -10,6 +11,7 @@ use chext::{
 use clap::{App, Arg};
 use rand::prelude::*;
 use rayon::iter::{ParallelBridge, ParallelIterator};
+use roaring::RoaringBitmap;
 use rustc_hash::FxHashMap;
 
 struct Args<'a> {
@@ -23,6 +25,10 @@ struct Args<'a> {
     epsilon: f64,
 
     geom_check_percent: Option<f64>,
+
+    /// build a persistent distance oracle from the WSPD and store it here instead of discarding
+    /// the decomposition once the statistics below are printed
+    oracle_out: Option<&'a str>,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -45,6 +51,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .arg(Arg::with_name("geom check")
             .long("geom_check")
             .takes_value(true))
+        .arg(Arg::with_name("oracle out")
+            .long("oracle_out")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("build a distance oracle from the WSPD and store it here"))
         .get_matches();
 
     let args = Args {
@@ -59,6 +70,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .parse()
             .expect("no valid epsilon provided"),
         geom_check_percent: matches.value_of("geom check").and_then(|d| d.parse().ok()),
+        oracle_out: matches.value_of("oracle out"),
     };
 
     println!("running with d={} and e={}", args.maxdepth, args.epsilon);
@@ -91,11 +103,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 3. verification code
     let now = Instant::now();
 
-    // point covering error
+    // point covering error. cell point sets are roaring bitmaps, so the size of each side of a
+    // pair is an O(1) cardinality lookup instead of `QuadTree::size`'s recursive subtree walk.
     let wspd_point_pairs_count = wspd
         .iter()
         .par_bridge()
-        .map(|(u, v)| u.size() * v.size())
+        .map(|(u, v)| u.node_bitmap().len() as usize * v.node_bitmap().len() as usize)
         .sum::<usize>();
     let all_pairs_count =
         (0.5 * graph.num_nodes() as f64 * (graph.num_nodes() - 1) as f64) as usize;
@@ -110,7 +123,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut point_pairs_counts = FxHashMap::default();
     for (u, v) in wspd.iter() {
         let pair_level = pair_level(u, v);
-        *point_pairs_counts.entry(pair_level).or_insert(0) += u.size() * v.size();
+        *point_pairs_counts.entry(pair_level).or_insert(0) +=
+            u.node_bitmap().len() as usize * v.node_bitmap().len() as usize;
     }
     println!("#pairs per depth: {:#?}", point_pairs_counts);
 
@@ -120,6 +134,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     println!("verification time: {:?}", now.elapsed());
 
+    if let Some(oracle_out) = args.oracle_out {
+        let now = Instant::now();
+        let oracle = DistanceOracle::new(&graph, &quadtree, &wspd);
+        oracle.to_file_binary(oracle_out)?;
+        println!("distance oracle built and stored. duration: {:?}", now.elapsed());
+    }
+
     // print cell size statistics
     let mut hist = vec![0; args.maxdepth + 1];
 
@@ -187,20 +208,26 @@ fn geometric_error_check<N: BaseNode, E: CHEdge>(
                     return None; // no paths found => there is no geometric error
                 }
 
-                // check for intersections by counting the occ of nodes.
-                let mut histogram = FxHashMap::default();
-                for node in paths.iter().flatten() {
-                    *histogram.entry(node).or_insert(0) += 1;
+                // check for intersections: AND each path's node-id bitmap together. a node common
+                // to every path survives the intersection, meaning the pair's separated cells
+                // already cover the detour and there's no geometric error.
+                let mut path_bitmaps = paths
+                    .iter()
+                    .map(|path| path.iter().map(|&n| u32::from(n)).collect::<RoaringBitmap>());
+                let mut intersection = path_bitmaps.next().unwrap_or_default();
+                for bitmap in path_bitmaps {
+                    intersection &= bitmap;
                 }
 
-                for (_, &occ) in &histogram {
-                    if occ == num_paths {
-                        return None; // a node is visited by each path => no geometric error
-                    }
+                if !intersection.is_empty() {
+                    return None; // a node is visited by each path => no geometric error
                 }
 
                 // there is a geom error => return the depth and weight of this cell
-                Some((pair_depth, u.size() * v.size()))
+                Some((
+                    pair_depth,
+                    u.node_bitmap().len() as usize * v.node_bitmap().len() as usize,
+                ))
             },
         )
         .filter_map(|res| res)
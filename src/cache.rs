@@ -0,0 +1,37 @@
+use sha3::{Digest, Sha3_256};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// sidecar file that stores the cache key backing `result_file`, written next to it
+fn key_path(result_file: &str) -> String {
+    [result_file, ".sha3"].concat()
+}
+
+/// content-addressed cache key for a long-running binary's output: a SHA3-256 digest over
+/// whatever inputs determine the result (e.g. the graph/paths file bytes plus the run
+/// parameters, formatted deterministically), as lowercase hex. mirrors `StoreableGraph`'s
+/// fingerprint-sidecar caching in spirit, but hashes real content instead of size/mtime, since
+/// here the run parameters - not just "did the source file change" - decide whether a
+/// previously written result is still valid.
+pub fn content_key(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha3_256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// true if `result_file` exists and its sidecar key file matches `key` - a previous run with
+/// identical inputs already produced `result_file`, so the caller can skip recomputing and reuse
+/// it as-is
+pub fn is_current(result_file: &str, key: &str) -> bool {
+    Path::new(result_file).exists()
+        && fs::read_to_string(key_path(result_file)).map_or(false, |stored| stored == key)
+}
+
+/// records `key` as the hash backing `result_file`, so a future run with identical inputs can
+/// reuse it via `is_current` instead of recomputing
+pub fn store_key(result_file: &str, key: &str) -> io::Result<()> {
+    fs::write(key_path(result_file), key)
+}
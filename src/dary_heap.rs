@@ -0,0 +1,107 @@
+//! a generic d-ary max-heap: like `std::collections::BinaryHeap`, but each node has `d` children
+//! instead of 2. for push-heavy workloads this gives a shallower tree (insert is O(log_d n)) at
+//! the cost of touching `d` children per level on pop - a good tradeoff when pushes outnumber
+//! pops, such as `hittingset::scan_edges_explore`'s CH-edge expansion.
+
+/// a d-ary max-heap over `T: Ord`, backed by a flat `Vec` the same way `BinaryHeap` is.
+pub struct DaryHeap<T: Ord> {
+    d: usize,
+    data: Vec<T>,
+}
+
+impl<T: Ord> DaryHeap<T> {
+    pub fn new(d: usize) -> Self {
+        assert!(d >= 2, "d-ary heap needs at least 2 children per node, got {}", d);
+        Self {
+            d,
+            data: Vec::new(),
+        }
+    }
+
+    /// builds a heap from an already-collected `Vec`, heapifying in place in O(n).
+    pub fn from_vec(d: usize, data: Vec<T>) -> Self {
+        assert!(d >= 2, "d-ary heap needs at least 2 children per node, got {}", d);
+        let mut heap = Self { d, data };
+        if heap.data.len() > 1 {
+            let last_parent = (heap.data.len() - 2) / heap.d;
+            for i in (0..=last_parent).rev() {
+                heap.sift_down(i);
+            }
+        }
+        heap
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// removes every element without changing the heap's arity.
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// the greatest element, if any, without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.len().checked_sub(1)?;
+        self.data.swap(0, last);
+        let top = self.data.pop();
+        if !self.data.is_empty() {
+            self.sift_down(0);
+        }
+        top
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / self.d;
+            if self.data[i] <= self.data[parent] {
+                break;
+            }
+            self.data.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let first_child = i * self.d + 1;
+            if first_child >= self.data.len() {
+                break;
+            }
+            let last_child = (first_child + self.d).min(self.data.len());
+
+            let mut largest = i;
+            for c in first_child..last_child {
+                if self.data[c] > self.data[largest] {
+                    largest = c;
+                }
+            }
+            if largest == i {
+                break;
+            }
+            self.data.swap(i, largest);
+            i = largest;
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for DaryHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
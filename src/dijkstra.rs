@@ -2,15 +2,23 @@ use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
 use crate::{
-    graph::{BaseNode, CHDirection, CHEdge, CHGraph, EdgeId, NodeId},
+    dary_heap::DaryHeap,
+    graph::{BaseNode, CHDirection, CHEdge, CHGraph, EdgeId, GeoNode, NodeId},
+    landmarks::{haversine_meters, Landmarks},
     paths::CHEdgeList,
 };
 
+/// arity of `frontier_fwd`/`frontier_bwd`'s d-ary heap. the "double insertion check" lazy
+/// deletion in `ch_fwd_step`/`ch_bwd_step` lets a lot of stale entries pile up, so a shallower
+/// tree than a binary heap's cuts comparisons on the continental-scale graphs this runs on; kept
+/// as a build-time constant so it's easy to benchmark other values.
+const CH_FRONTIER_HEAP_D: usize = 4;
+
 pub struct Dijkstra<'a, N, E> {
-    frontier_fwd: BinaryHeap<HeapElement>, // frontier, cost, and prev node data for the forward and backward search
-    cost_fwd: Vec<Option<u32>>,            // structures are reused in each run
+    frontier_fwd: DaryHeap<HeapElement>, // frontier, cost, and prev node data for the forward and backward search
+    cost_fwd: Vec<Option<u32>>,          // structures are reused in each run
     prev_fwd: Vec<Option<(NodeId, EdgeId)>>,
-    frontier_bwd: BinaryHeap<HeapElement>,
+    frontier_bwd: DaryHeap<HeapElement>,
     cost_bwd: Vec<Option<u32>>,
     prev_bwd: Vec<Option<(NodeId, EdgeId)>>,
     graph: &'a dyn CHGraph<Node = N, Edge = E>,
@@ -53,12 +61,18 @@ where
     E: CHEdge,
 {
     pub fn new(graph: &'a dyn CHGraph<Node = N, Edge = E>) -> Self {
+        Self::new_with_arity(graph, CH_FRONTIER_HEAP_D)
+    }
+
+    /// like `new`, but lets the caller pick the arity of `frontier_fwd`/`frontier_bwd`'s d-ary
+    /// heap instead of the default `CH_FRONTIER_HEAP_D`, for benchmarking other fan-outs.
+    pub fn new_with_arity(graph: &'a dyn CHGraph<Node = N, Edge = E>, heap_d: usize) -> Self {
         Self {
             cost_fwd: vec![None; graph.num_nodes()],
-            frontier_fwd: BinaryHeap::new(),
+            frontier_fwd: DaryHeap::new(heap_d),
             prev_fwd: vec![None; graph.num_nodes()],
             cost_bwd: vec![None; graph.num_nodes()],
-            frontier_bwd: BinaryHeap::new(),
+            frontier_bwd: DaryHeap::new(heap_d),
             prev_bwd: vec![None; graph.num_nodes()],
             graph: graph.into(),
             visited: Vec::new(),
@@ -273,4 +287,581 @@ where
         }
         Some(entry.id)
     }
+
+    /// goal-directed A* search using the ALT (A*, Landmarks, Triangle inequality) heuristic.
+    /// unlike `ch_search` this does not rely on the CH hierarchy: it explores `CHDirection::Both`
+    /// edges directly, guided by `landmarks.heuristic(v, dest)`. the heuristic is admissible and
+    /// consistent, so the search can stop as soon as `dest` is popped.
+    /// returns: (distance, edges) or None if there is no path
+    pub fn alt_search(
+        &mut self,
+        landmarks: &Landmarks,
+        start: NodeId,
+        dest: NodeId,
+    ) -> Option<(u32, CHEdgeList)> {
+        self.reset();
+
+        let mut settled = vec![false; self.graph.num_nodes()];
+        let mut frontier = BinaryHeap::new();
+
+        self.cost_fwd[start] = Some(0);
+        self.visited.push(start);
+        frontier.push(AStarHeapElement {
+            priority: landmarks.heuristic(start, dest),
+            cost: 0,
+            id: start,
+            prev: None,
+        });
+
+        while let Some(AStarHeapElement { cost, id, prev, .. }) = frontier.pop() {
+            if settled[id] {
+                continue; // stale entry, lazily dropped
+            }
+            settled[id] = true;
+            self.prev_fwd[id] = prev;
+
+            if id == dest {
+                let mut path = Vec::new();
+                let mut next_id = dest;
+                while let Some(prev) = self.prev_fwd[next_id] {
+                    path.push(prev.1);
+                    next_id = prev.0;
+                }
+                path.reverse();
+                return Some((cost, CHEdgeList::new(path)));
+            }
+
+            for &edge in self.graph.out_edges(id, CHDirection::Both) {
+                let e = self.graph.edge(edge);
+                let next_cost = cost + e.cost();
+                if self.cost_fwd[e.target()].map_or(true, |c| next_cost < c) {
+                    self.cost_fwd[e.target()] = Some(next_cost);
+                    self.visited.push(e.target());
+                    frontier.push(AStarHeapElement {
+                        priority: next_cost + landmarks.heuristic(e.target(), dest),
+                        cost: next_cost,
+                        id: e.target(),
+                        prev: Some((id, edge)),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// runs a plain single-source Dijkstra over the base graph - not the CH hierarchy, so it
+    /// explores `CHDirection::Both` edges directly, same as `alt_search` - settling every node
+    /// reachable from `start` and returning them in the order they were settled (non-decreasing
+    /// distance from `start`). used by `random_pairs::DijkstraRankGenerator` to bucket sampled
+    /// queries by Dijkstra rank.
+    pub fn single_source_settle_order(&mut self, start: NodeId) -> Vec<NodeId> {
+        self.reset();
+
+        let mut settled = vec![false; self.graph.num_nodes()];
+        let mut frontier = BinaryHeap::new();
+        let mut order = Vec::new();
+
+        self.cost_fwd[start] = Some(0);
+        self.visited.push(start);
+        frontier.push(HeapElement {
+            id: start,
+            cost: 0,
+            prev: None,
+        });
+
+        while let Some(HeapElement { cost, id, .. }) = frontier.pop() {
+            if settled[id] {
+                continue; // stale entry, lazily dropped
+            }
+            settled[id] = true;
+            order.push(id);
+
+            for &edge in self.graph.out_edges(id, CHDirection::Both) {
+                let e = self.graph.edge(edge);
+                let next_cost = cost + e.cost();
+                if self.cost_fwd[e.target()].map_or(true, |c| next_cost < c) {
+                    self.cost_fwd[e.target()] = Some(next_cost);
+                    self.visited.push(e.target());
+                    frontier.push(HeapElement {
+                        id: e.target(),
+                        cost: next_cost,
+                        prev: None,
+                    });
+                }
+            }
+        }
+        order
+    }
+
+    /// finds the shortest CH path from `start` to `dest` that visits every node in `waypoints`,
+    /// in whatever order minimizes the total cost.
+    /// builds an all-pairs distance matrix among the terminals with `ch_search`, then solves the
+    /// visiting order: brute force for few waypoints, Held-Karp dynamic programming otherwise.
+    /// returns: (distance, edges), or None if any two consecutive stops are disconnected
+    pub fn tour_search(
+        &mut self,
+        start: NodeId,
+        waypoints: &[NodeId],
+        dest: NodeId,
+    ) -> Option<(u32, CHEdgeList)> {
+        let terminals: Vec<NodeId> = std::iter::once(start)
+            .chain(waypoints.iter().copied())
+            .chain(std::iter::once(dest))
+            .collect();
+        let k = terminals.len();
+        let w = waypoints.len(); // terminals[1..=w] are the waypoints, terminals[0]=start, terminals[k-1]=dest
+
+        // all-pairs distance matrix among the terminals
+        let mut dist = vec![vec![None; k]; k];
+        for i in 0..k {
+            for j in 0..k {
+                if i != j {
+                    dist[i][j] = self.ch_search(terminals[i], terminals[j]).map(|(d, _)| d);
+                }
+            }
+        }
+
+        // order[0..w] is a permutation of 1..=w (indices into `terminals`) describing the
+        // waypoint visiting order between start (index 0) and dest (index k-1)
+        let order = if w <= 9 {
+            brute_force_order(&dist, w)?
+        } else {
+            held_karp_order(&dist, w)?
+        };
+
+        let mut stops = vec![0];
+        stops.extend(order);
+        stops.push(k - 1);
+
+        // stitch the concrete path by concatenating each leg's CH search
+        let mut total = 0;
+        let mut edges = Vec::new();
+        for leg in stops.windows(2) {
+            let (a, b) = (leg[0], leg[1]);
+            let (leg_cost, mut leg_edges) = self.ch_search(terminals[a], terminals[b])?;
+            total += leg_cost;
+            edges.append(&mut leg_edges.list);
+        }
+        Some((total, CHEdgeList::new(edges)))
+    }
+
+    /// finds the shortest CH path visiting every node in `waypoints`, in whatever order
+    /// minimizes total cost, with no separate start/dest: unlike `tour_search`, the path just
+    /// begins and ends at whichever waypoints the optimal order picks. if `fixed_start` is true,
+    /// `waypoints[0]` is pinned as the first stop instead of being free to reorder.
+    /// builds an all-pairs distance matrix among the waypoints with `ch_search`, then solves the
+    /// visiting order: brute force for few waypoints, Held-Karp dynamic programming otherwise.
+    /// returns: (distance, edges), or None if any two consecutive stops are disconnected
+    pub fn waypoint_search(
+        &mut self,
+        waypoints: &[NodeId],
+        fixed_start: bool,
+    ) -> Option<(u32, CHEdgeList)> {
+        let k = waypoints.len();
+        let mut dist = vec![vec![None; k]; k];
+        for i in 0..k {
+            for j in 0..k {
+                if i != j {
+                    dist[i][j] = self
+                        .ch_search(waypoints[i], waypoints[j])
+                        .map(|(d, _)| d);
+                }
+            }
+        }
+
+        let order = if k <= 9 {
+            brute_force_tour(&dist, fixed_start)?
+        } else {
+            held_karp_tour(&dist, fixed_start)?
+        };
+
+        let mut total = 0;
+        let mut edges = Vec::new();
+        for leg in order.windows(2) {
+            let (a, b) = (leg[0], leg[1]);
+            let (leg_cost, mut leg_edges) = self.ch_search(waypoints[a], waypoints[b])?;
+            total += leg_cost;
+            edges.append(&mut leg_edges.list);
+        }
+        Some((total, CHEdgeList::new(edges)))
+    }
+
+    /// computes the full `sources.len() x targets.len()` shortest-distance table using the
+    /// standard bucket-based many-to-many CH algorithm. first runs a one-sided backward CH search
+    /// to completion from every target, bucketing `(target_index, dist(v, target))` at every node
+    /// `v` it settles; then runs a one-sided forward CH search to completion from every source
+    /// and, at every node `v` it settles, scans `v`'s bucket to relax
+    /// `matrix[s][t] = min(matrix[s][t], dist(s, v) + dist(v, t))`. reuses `ch_fwd_step`/
+    /// `ch_bwd_step` as-is, so the stall-on-demand checks carry over unchanged; buckets are local
+    /// to the call, so nothing needs clearing between invocations.
+    pub fn ch_matrix(&mut self, sources: &[NodeId], targets: &[NodeId]) -> Vec<Vec<Option<u32>>> {
+        let mut matrix = vec![vec![None; targets.len()]; sources.len()];
+        let mut buckets: Vec<Vec<(usize, u32)>> = vec![Vec::new(); self.graph.num_nodes()];
+
+        for (t_index, &target) in targets.iter().enumerate() {
+            self.reset();
+            self.frontier_bwd.push(HeapElement {
+                id: target,
+                cost: 0,
+                prev: None,
+            });
+            while !self.frontier_bwd.is_empty() {
+                self.ch_bwd_step();
+            }
+            for &v in &self.visited {
+                if let Some(dist_vt) = self.cost_bwd[v] {
+                    buckets[v].push((t_index, dist_vt));
+                }
+            }
+        }
+
+        for (s_index, &source) in sources.iter().enumerate() {
+            self.reset();
+            self.frontier_fwd.push(HeapElement {
+                id: source,
+                cost: 0,
+                prev: None,
+            });
+            while !self.frontier_fwd.is_empty() {
+                self.ch_fwd_step();
+            }
+            for &v in &self.visited {
+                let Some(dist_sv) = self.cost_fwd[v] else {
+                    continue;
+                };
+                for &(t_index, dist_vt) in &buckets[v] {
+                    let candidate = dist_sv + dist_vt;
+                    if matrix[s_index][t_index].map_or(true, |best| candidate < best) {
+                        matrix[s_index][t_index] = Some(candidate);
+                    }
+                }
+            }
+        }
+
+        matrix
+    }
+}
+
+impl<'a, N, E> Dijkstra<'a, N, E>
+where
+    N: BaseNode + GeoNode,
+    E: CHEdge,
+{
+    /// goal-directed A* search using a plain straight-line heuristic instead of `alt_search`'s
+    /// landmark tables: `h(v) = haversine_meters(v, dest) * min_cost_per_meter`, which stays
+    /// admissible as long as `min_cost_per_meter` really is a lower bound on the graph's
+    /// cost-per-meter (see `landmarks::min_cost_per_meter`). weaker than the ALT heuristic - it
+    /// ignores the road network entirely - but needs no precomputation, so it's the cheaper choice
+    /// when a graph's landmark tables haven't been built yet.
+    /// returns: (distance, edges) or None if there is no path
+    pub fn geo_search(
+        &mut self,
+        min_cost_per_meter: f64,
+        start: NodeId,
+        dest: NodeId,
+    ) -> Option<(u32, CHEdgeList)> {
+        self.reset();
+
+        let dest_node = self.graph.node(dest);
+        let (dest_lat, dest_lon) = (dest_node.lat(), dest_node.lon());
+        let heuristic = |id: NodeId| {
+            let node = self.graph.node(id);
+            let meters = haversine_meters(node.lat(), node.lon(), dest_lat, dest_lon);
+            (meters as f64 * min_cost_per_meter) as u32
+        };
+
+        let mut settled = vec![false; self.graph.num_nodes()];
+        let mut frontier = BinaryHeap::new();
+
+        self.cost_fwd[start] = Some(0);
+        self.visited.push(start);
+        frontier.push(AStarHeapElement {
+            priority: heuristic(start),
+            cost: 0,
+            id: start,
+            prev: None,
+        });
+
+        while let Some(AStarHeapElement { cost, id, prev, .. }) = frontier.pop() {
+            if settled[id] {
+                continue; // stale entry, lazily dropped
+            }
+            settled[id] = true;
+            self.prev_fwd[id] = prev;
+
+            if id == dest {
+                let mut path = Vec::new();
+                let mut next_id = dest;
+                while let Some(prev) = self.prev_fwd[next_id] {
+                    path.push(prev.1);
+                    next_id = prev.0;
+                }
+                path.reverse();
+                return Some((cost, CHEdgeList::new(path)));
+            }
+
+            for &edge in self.graph.out_edges(id, CHDirection::Both) {
+                let e = self.graph.edge(edge);
+                let next_cost = cost + e.cost();
+                if self.cost_fwd[e.target()].map_or(true, |c| next_cost < c) {
+                    self.cost_fwd[e.target()] = Some(next_cost);
+                    self.visited.push(e.target());
+                    frontier.push(AStarHeapElement {
+                        priority: next_cost + heuristic(e.target()),
+                        cost: next_cost,
+                        id: e.target(),
+                        prev: Some((id, edge)),
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// enumerates all permutations of `1..=w` and returns the one minimizing the total leg cost
+/// `dist[0][p0] + dist[p0][p1] + ... + dist[pw-1][w+1]`
+fn brute_force_order(dist: &[Vec<Option<u32>>], w: usize) -> Option<Vec<usize>> {
+    let dest = dist.len() - 1;
+    let mut indices: Vec<usize> = (1..=w).collect();
+    let mut best: Option<(u32, Vec<usize>)> = None;
+
+    permutations(&mut indices, &mut |perm| {
+        let mut cost = 0u32;
+        let mut prev = 0;
+        let mut ok = true;
+        for &next in perm.iter().chain(std::iter::once(&dest)) {
+            match dist[prev][next] {
+                Some(d) => cost += d,
+                None => {
+                    ok = false;
+                    break;
+                }
+            }
+            prev = next;
+        }
+        if ok && best.as_ref().map_or(true, |(b, _)| cost < *b) {
+            best = Some((cost, perm.to_vec()));
+        }
+    });
+
+    best.map(|(_, perm)| perm)
+}
+
+/// calls `f` once for every permutation of `items` (Heap's algorithm)
+fn permutations(items: &mut Vec<usize>, f: &mut impl FnMut(&[usize])) {
+    fn go(items: &mut Vec<usize>, k: usize, f: &mut impl FnMut(&[usize])) {
+        if k == 1 {
+            f(items);
+            return;
+        }
+        for i in 0..k {
+            go(items, k - 1, f);
+            if k % 2 == 0 {
+                items.swap(i, k - 1);
+            } else {
+                items.swap(0, k - 1);
+            }
+        }
+    }
+    let len = items.len();
+    if len == 0 {
+        f(items);
+    } else {
+        go(items, len, f);
+    }
+}
+
+/// Held-Karp DP over the waypoint set: dp[mask][i] = cheapest path starting at terminal 0,
+/// visiting exactly the waypoints in `mask`, ending at waypoint `i` (1-indexed into `dist`)
+fn held_karp_order(dist: &[Vec<Option<u32>>], w: usize) -> Option<Vec<usize>> {
+    let dest = dist.len() - 1;
+    let full_mask = (1 << w) - 1;
+
+    // dp[mask][i] / parent[mask][i], i in 0..w corresponds to waypoint terminal index i+1
+    let mut dp = vec![vec![None; w]; 1 << w];
+    let mut parent = vec![vec![None; w]; 1 << w];
+
+    for i in 0..w {
+        if let Some(d) = dist[0][i + 1] {
+            dp[1 << i][i] = Some(d);
+        }
+    }
+
+    for mask in 1..=full_mask {
+        for i in 0..w {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+            let Some(cur) = dp[mask][i] else { continue };
+            for j in 0..w {
+                if mask & (1 << j) != 0 {
+                    continue;
+                }
+                if let Some(d) = dist[i + 1][j + 1] {
+                    let next_mask = mask | (1 << j);
+                    let candidate = cur + d;
+                    if dp[next_mask][j].map_or(true, |best| candidate < best) {
+                        dp[next_mask][j] = Some(candidate);
+                        parent[next_mask][j] = Some(i);
+                    }
+                }
+            }
+        }
+    }
+
+    // close the tour to dest, find the best ending waypoint
+    let (_, last) = (0..w)
+        .filter_map(|i| {
+            let cost = dp[full_mask][i]?;
+            let to_dest = dist[i + 1][dest]?;
+            Some((cost + to_dest, i))
+        })
+        .min_by_key(|&(cost, _)| cost)?;
+
+    // backtrack
+    let mut order = Vec::with_capacity(w);
+    let mut mask = full_mask;
+    let mut i = last;
+    loop {
+        order.push(i + 1);
+        match parent[mask][i] {
+            Some(prev) => {
+                mask &= !(1 << i);
+                i = prev;
+            }
+            None => break,
+        }
+    }
+    order.reverse();
+    Some(order)
+}
+
+/// enumerates tours over `0..dist.len()` (permutations of `1..k` with `0` pinned first if
+/// `fixed_start`, otherwise permutations of the full `0..k`) and returns the order minimizing
+/// total leg cost `dist[order[0]][order[1]] + dist[order[1]][order[2]] + ...`
+fn brute_force_tour(dist: &[Vec<Option<u32>>], fixed_start: bool) -> Option<Vec<usize>> {
+    let k = dist.len();
+    let mut best: Option<(u32, Vec<usize>)> = None;
+
+    let mut try_order = |order: &[usize]| {
+        let mut cost = 0u32;
+        let mut ok = true;
+        for leg in order.windows(2) {
+            match dist[leg[0]][leg[1]] {
+                Some(d) => cost += d,
+                None => {
+                    ok = false;
+                    break;
+                }
+            }
+        }
+        if ok && best.as_ref().map_or(true, |(b, _)| cost < *b) {
+            best = Some((cost, order.to_vec()));
+        }
+    };
+
+    if fixed_start {
+        let mut rest: Vec<usize> = (1..k).collect();
+        permutations(&mut rest, &mut |perm| {
+            let mut order = vec![0];
+            order.extend(perm);
+            try_order(&order);
+        });
+    } else {
+        let mut indices: Vec<usize> = (0..k).collect();
+        permutations(&mut indices, &mut |perm| try_order(perm));
+    }
+
+    best.map(|(_, order)| order)
+}
+
+/// Held-Karp DP over the waypoint set, generalized to an open tour with no fixed destination:
+/// dp[mask][i] = cheapest path visiting exactly the waypoints in `mask`, ending at `i`. if
+/// `fixed_start` is true, only tours starting at waypoint 0 are considered; otherwise the
+/// cheapest starting waypoint is chosen along with everything else.
+fn held_karp_tour(dist: &[Vec<Option<u32>>], fixed_start: bool) -> Option<Vec<usize>> {
+    let k = dist.len();
+    let full_mask = (1 << k) - 1;
+
+    let mut dp = vec![vec![None; k]; 1 << k];
+    let mut parent = vec![vec![None; k]; 1 << k];
+
+    for i in 0..k {
+        if fixed_start && i != 0 {
+            continue;
+        }
+        dp[1 << i][i] = Some(0);
+    }
+
+    for mask in 1..=full_mask {
+        for i in 0..k {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+            let Some(cur) = dp[mask][i] else { continue };
+            for j in 0..k {
+                if mask & (1 << j) != 0 {
+                    continue;
+                }
+                if let Some(d) = dist[i][j] {
+                    let next_mask = mask | (1 << j);
+                    let candidate = cur + d;
+                    if dp[next_mask][j].map_or(true, |best| candidate < best) {
+                        dp[next_mask][j] = Some(candidate);
+                        parent[next_mask][j] = Some(i);
+                    }
+                }
+            }
+        }
+    }
+
+    let (_, last) = (0..k)
+        .filter_map(|i| Some((dp[full_mask][i]?, i)))
+        .min_by_key(|&(cost, _)| cost)?;
+
+    let mut order = Vec::with_capacity(k);
+    let mut mask = full_mask;
+    let mut i = last;
+    loop {
+        order.push(i);
+        match parent[mask][i] {
+            Some(prev) => {
+                mask &= !(1 << i);
+                i = prev;
+            }
+            None => break,
+        }
+    }
+    order.reverse();
+    Some(order)
+}
+
+// entry for the A* frontier: ordered by f = g + h (smallest first, BinaryHeap is a maxheap)
+struct AStarHeapElement {
+    priority: u32,
+    cost: u32,
+    id: NodeId,
+    prev: Option<(NodeId, EdgeId)>,
+}
+
+impl Ord for AStarHeapElement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.priority.cmp(&other.priority) {
+            Ordering::Greater => Ordering::Less,
+            Ordering::Less => Ordering::Greater,
+            Ordering::Equal => self.id.cmp(&other.id).reverse(),
+        }
+    }
+}
+impl PartialOrd for AStarHeapElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Eq for AStarHeapElement {}
+impl PartialEq for AStarHeapElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
 }
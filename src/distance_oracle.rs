@@ -0,0 +1,123 @@
+//! a persistent approximate distance oracle built from a `WSPD` over a `QuadTree`: for every
+//! well-separated pair of cells, one representative CH search is run once up front, so later
+//! queries are an O(1) lookup instead of a fresh search. the well-separation invariant (cell
+//! diameters <= e * inter-cell distance) is what keeps the result within a (1+O(e)) factor of the
+//! true shortest-path cost.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dijkstra::Dijkstra,
+    graph::{BaseNode, CHEdge, CHGraph, NodeId, StoreableGraph},
+    quadtree::{CellId, QuadTree, TreeNode},
+    wspd::{Tree, WSPD},
+};
+
+/// a pair of `CellId`s, canonicalized so lookups don't care which side of the WSPD pair they came
+/// from
+type PairKey = (CellId, CellId);
+
+fn pair_key(a: CellId, b: CellId) -> PairKey {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DistanceOracle {
+    /// approximate distance between the two cells' representative nodes, keyed by `pair_key`
+    pair_distance: FxHashMap<PairKey, u32>,
+    /// every cell that appears on either side of some pair, mapped to the pairs it takes part in
+    cell_pairs: FxHashMap<CellId, Vec<PairKey>>,
+    /// each node's own (finest) quadtree cell, so `dist` can walk its ancestor chain
+    node_cell: FxHashMap<NodeId, CellId>,
+}
+
+impl DistanceOracle {
+    /// builds the oracle from an already-computed WSPD: one representative node per cell
+    /// (`QuadTree::rep`, the point with the largest CH level) and one `Dijkstra::ch_search` per
+    /// pair, trading a single precomputed distance per pair for O(1) approximate queries.
+    pub fn new<N: BaseNode, E: CHEdge>(
+        graph: &dyn CHGraph<Node = N, Edge = E>,
+        quadtree: &QuadTree,
+        wspd: &WSPD<QuadTree>,
+    ) -> Self {
+        let mut dijkstra = Dijkstra::new(graph);
+        let mut pair_distance = FxHashMap::default();
+        let mut cell_pairs: FxHashMap<CellId, Vec<PairKey>> = FxHashMap::default();
+
+        for (u, v) in wspd.iter() {
+            let key = pair_key(u.id, v.id);
+            let dist = dijkstra
+                .ch_search(u.rep().id(), v.rep().id())
+                .map(|(dist, _)| dist)
+                .unwrap_or(u32::MAX);
+            pair_distance.insert(key, dist);
+            cell_pairs.entry(u.id).or_default().push(key);
+            cell_pairs.entry(v.id).or_default().push(key);
+        }
+
+        let mut node_cell = FxHashMap::default();
+        collect_leaf_cells(quadtree, &mut node_cell);
+
+        Self {
+            pair_distance,
+            cell_pairs,
+            node_cell,
+        }
+    }
+
+    /// locates the unique well-separated pair that puts `s` and `t` on opposite sides and returns
+    /// the (1+O(e)) approximate distance stored for it, or `None` if either node fell outside the
+    /// quadtree the oracle was built from.
+    pub fn dist(&self, s: NodeId, t: NodeId) -> Option<u32> {
+        let s_cell = *self.node_cell.get(&s)?;
+        let t_cell = *self.node_cell.get(&t)?;
+        let t_pairs: FxHashSet<PairKey> = self.ancestor_pairs(t_cell).into_iter().collect();
+        self.ancestor_pairs(s_cell)
+            .into_iter()
+            .find(|pair| t_pairs.contains(pair))
+            .and_then(|pair| self.pair_distance.get(&pair).copied())
+    }
+
+    /// every pair that `cell` or one of its ancestors takes part in, walked from `cell` up to the
+    /// root - bounded by the quadtree's depth, hence "the (few) pairs" a node's chain touches.
+    fn ancestor_pairs(&self, mut cell: CellId) -> Vec<PairKey> {
+        let mut pairs = Vec::new();
+        loop {
+            if let Some(p) = self.cell_pairs.get(&cell) {
+                pairs.extend(p.iter().copied());
+            }
+            if cell.is_empty() {
+                break;
+            }
+            cell = cell.parent();
+        }
+        pairs
+    }
+}
+
+impl StoreableGraph for DistanceOracle {
+    fn from_file_binary(filename: &str) -> Result<Self, bincode::Error> {
+        let file = std::fs::File::open(filename)?;
+        let reader = std::io::BufReader::new(file);
+        bincode::deserialize_from(reader)
+    }
+}
+
+/// assigns every point to the id of the finest (childless) cell that directly holds it
+fn collect_leaf_cells<'a>(tree: &'a QuadTree<'a>, out: &mut FxHashMap<NodeId, CellId>) {
+    let mut has_children = false;
+    for child in tree.children() {
+        has_children = true;
+        collect_leaf_cells(child, out);
+    }
+    if !has_children {
+        for point in tree.points() {
+            out.insert(point.id(), tree.id);
+        }
+    }
+}
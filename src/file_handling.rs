@@ -3,8 +3,10 @@ use std::io::prelude::*;
 use std::io::{self, BufReader, BufWriter};
 
 use bincode::{deserialize_from, serialize_into};
+use rayon::prelude::*;
 
 use crate::graph::{FMIEdge, FMIGraph, FMINode};
+use crate::paths::CHEdgeList;
 
 impl FMIGraph {
     /// reader for a .fmi file containing a CH graph
@@ -71,6 +73,44 @@ impl FMIGraph {
         Self::from_fmi_csv(header_nodes, header_edges, reader)
     }
 
+    /// same format as `from_fmi_maxspeed_ch_txt`, but parses the node and edge blocks in
+    /// parallel instead of record-by-record. worthwhile on continent-sized graphs where the
+    /// sequential `csv::Reader` loop dominates load time.
+    pub fn from_fmi_maxspeed_ch_txt_parallel(
+        filename: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // check metadata
+        let file = File::open(filename)?;
+        let file = BufReader::new(file);
+
+        for line in file.lines() {
+            let line = line?;
+            if line.starts_with("#") {
+                if line.contains(" Type ") && !line.contains("chgraph") {
+                    return Err(Box::new(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "wrong file type",
+                    )));
+                }
+            }
+            if line.is_empty() {
+                break;
+            }
+        }
+
+        // setup header
+        let header_nodes =
+            csv::StringRecord::from(vec!["id", "osm", "lat", "lon", "elevation", "level"]);
+        let header_edges = csv::StringRecord::from(vec![
+            "source", "target", "cost", "type", "maxspeed", "child1", "child2", "id",
+        ]);
+
+        let file = File::open(filename)?;
+        let file = BufReader::new(file);
+
+        Self::from_fmi_csv_parallel(header_nodes, header_edges, file)
+    }
+
     /// reader for a .fmi file containing a non-ch graph
     pub fn from_fmi_maxspeed_txt(filename: &str) -> Result<Self, Box<dyn std::error::Error>> {
         /*
@@ -132,6 +172,126 @@ impl FMIGraph {
         Self::from_fmi_csv(header_nodes, header_edges, reader)
     }
 
+    /// same format as `from_fmi_maxspeed_txt`, but parses the node and edge blocks in parallel.
+    /// see `from_fmi_maxspeed_ch_txt_parallel`.
+    pub fn from_fmi_maxspeed_txt_parallel(
+        filename: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // check metadata
+        let file = File::open(filename)?;
+        let file = BufReader::new(file);
+
+        for line in file.lines() {
+            let line = line?;
+            if line.starts_with("#") {
+                if line.contains(" Type ") && !line.contains("maxspeed") {
+                    return Err(Box::new(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "wrong file type",
+                    )));
+                }
+            }
+            if line.is_empty() {
+                break;
+            }
+        }
+
+        // setup header
+        let header_nodes = csv::StringRecord::from(vec!["id", "osm", "lat", "lon", "elevation"]);
+        let header_edges =
+            csv::StringRecord::from(vec!["source", "target", "cost", "type", "maxspeed", "id"]);
+
+        let file = File::open(filename)?;
+        let file = BufReader::new(file);
+
+        Self::from_fmi_csv_parallel(header_nodes, header_edges, file)
+    }
+
+    /// builds a graph from a plain edge-list file: one "source target [weight]" record per
+    /// line, whitespace separated, with no FMI metadata, OSM ids, or coordinates required.
+    /// `num_nodes` is inferred as the largest node id seen plus one, and a missing weight
+    /// defaults to 1. nodes otherwise have no coordinates, which is enough for `BaseGraph`/CH/HS
+    /// consumers but meaningless input for the GeoJSON vis path - pass `coords_filename` (one
+    /// "lat lon" record per line, in node-id order) to fill in real coordinates where the vis
+    /// path is needed.
+    pub fn from_edge_list_txt(
+        filename: &str,
+        coords_filename: Option<&str>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(filename)?;
+        let reader = BufReader::new(file);
+
+        let mut edges = Vec::new();
+        let mut max_node = 0usize;
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let source: usize = fields
+                .next()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing source id"))?
+                .parse()?;
+            let target: usize = fields
+                .next()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing target id"))?
+                .parse()?;
+            let cost: u32 = match fields.next() {
+                Some(weight) => weight.parse()?,
+                None => 1,
+            };
+
+            max_node = max_node.max(source).max(target);
+            edges.push(FMIEdge {
+                id: edges.len(),
+                source,
+                target,
+                cost,
+                child1: -1,
+                child2: -1,
+            });
+        }
+
+        let num_nodes = max_node + 1;
+        let mut nodes: Vec<FMINode> = (0..num_nodes)
+            .map(|id| FMINode {
+                id,
+                lat: 0.0,
+                lon: 0.0,
+                level: 0,
+            })
+            .collect();
+
+        if let Some(coords_filename) = coords_filename {
+            let file = File::open(coords_filename)?;
+            let reader = BufReader::new(file);
+            for (id, line) in reader.lines().enumerate() {
+                let line = line?;
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') || id >= nodes.len() {
+                    continue;
+                }
+
+                let mut fields = line.split_whitespace();
+                let lat: f64 = fields
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing lat"))?
+                    .parse()?;
+                let lon: f64 = fields
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing lon"))?
+                    .parse()?;
+                nodes[id].lat = lat;
+                nodes[id].lon = lon;
+            }
+        }
+
+        Ok(Self { nodes, edges })
+    }
+
     /// generic fmi csv reader, using headers set in other reader functions
     fn from_fmi_csv<R: std::io::Read>(
         header_nodes: csv::StringRecord,
@@ -167,6 +327,64 @@ impl FMIGraph {
         Ok(Self { nodes, edges })
     }
 
+    /// parallel counterpart to `from_fmi_csv`. reads the whole input up front, splits the node
+    /// and edge blocks on newlines, and parses each line independently with rayon instead of
+    /// stepping a `csv::Reader` one record at a time. nodes carry explicit ids, but edges derive
+    /// their `EdgeId` from line position, so both blocks stay in file order - `par_iter` parses
+    /// them in place, it just doesn't do so sequentially.
+    fn from_fmi_csv_parallel<R: std::io::Read>(
+        header_nodes: csv::StringRecord,
+        header_edges: csv::StringRecord,
+        mut reader: R,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf)?;
+
+        let mut lines = buf
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let n: usize = lines
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing node count"))?
+            .parse()?;
+        let m: usize = lines
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing edge count"))?
+            .parse()?;
+
+        let remaining: Vec<&str> = lines.collect();
+        if remaining.len() < n + m {
+            return Err(Box::new(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "truncated fmi file: fewer node/edge records than the declared counts",
+            )));
+        }
+        let (node_lines, rest) = remaining.split_at(n);
+        let edge_lines = &rest[..m];
+
+        let nodes: Vec<FMINode> = node_lines
+            .par_iter()
+            .map(|line| -> Result<FMINode, csv::Error> {
+                let record = csv::StringRecord::from(line.split_whitespace().collect::<Vec<_>>());
+                record.deserialize(Some(&header_nodes))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let edges: Vec<FMIEdge> = edge_lines
+            .par_iter()
+            .enumerate()
+            .map(|(i, line)| -> Result<FMIEdge, csv::Error> {
+                let mut record =
+                    csv::StringRecord::from(line.split_whitespace().collect::<Vec<_>>());
+                record.push_field(&i.to_string());
+                record.deserialize(Some(&header_edges))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { nodes, edges })
+    }
+
     pub fn to_file_binary(&self, filename: &str) -> Result<(), bincode::Error> {
         let file = File::create(filename)?;
         let mut writer = BufWriter::new(file);
@@ -179,3 +397,36 @@ impl FMIGraph {
         deserialize_from(reader)
     }
 }
+
+/// lazily yields `CHEdgeList` records from a paths CSV file one at a time, instead of
+/// `crate::load_paths`'s `Vec<CHEdgeList>`, so a verification pass can stream a paths corpus far
+/// larger than RAM without ever materializing the whole thing.
+pub struct PathReader {
+    rdr: csv::Reader<File>,
+    record: csv::StringRecord,
+}
+
+impl PathReader {
+    pub fn new(filename: &str) -> Result<Self, csv::Error> {
+        let rdr = csv::ReaderBuilder::new()
+            .flexible(true)
+            .has_headers(false)
+            .from_path(filename)?;
+        Ok(Self {
+            rdr,
+            record: csv::StringRecord::new(),
+        })
+    }
+}
+
+impl Iterator for PathReader {
+    type Item = Result<CHEdgeList, csv::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rdr.read_record(&mut self.record) {
+            Ok(true) => Some(self.record.deserialize(None)),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
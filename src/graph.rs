@@ -1,6 +1,10 @@
 mod chgraph;
 mod fmigraph;
 mod hsgraph;
+mod mmap;
+mod petgraph_adapter;
+#[cfg(feature = "postgis")]
+mod postgis;
 
 use chgraph::Edge as CH_Edge;
 use chgraph::Node as CH_Node;
@@ -19,6 +23,12 @@ use crate::paths::{CHEdgeList, EdgeList};
 pub use chgraph::AdjArrayGraph;
 pub use fmigraph::{FMIEdge, FMIGraph, FMINode};
 pub use hsgraph::ToporderedGraph;
+pub use mmap::{MmapFMIGraph, RawFMIEdge, RawFMINode};
+pub use petgraph_adapter::HSEdgeRef;
+#[cfg(feature = "petgraph-interop")]
+pub use petgraph_adapter::CHEdgeRef;
+#[cfg(feature = "postgis")]
+pub use postgis::PostgisConfig;
 
 // define some types for use in derive and index macros
 type NodeEdgePair = (NodeId, EdgeId);
@@ -62,6 +72,12 @@ impl From<NodeId> for String {
     }
 }
 
+impl From<NodeId> for u32 {
+    fn from(n: NodeId) -> u32 {
+        n.0 as u32
+    }
+}
+
 #[derive(
     Copy,
     Clone,
@@ -116,11 +132,6 @@ pub trait GeoNode {
     fn lon(&self) -> f64;
 }
 
-/// provides a parents() function to traverse the metagraph
-pub trait HSNode {
-    fn parents(&self) -> &[EdgeId];
-}
-
 pub trait CHNode {
     fn level(&self) -> u32;
 }
@@ -144,9 +155,10 @@ pub trait ChildEdge {
 
 pub trait CHEdge: BaseEdge + CostEdge + ChildEdge {}
 
-/// provides a parents() function to traverse the metagraph
+/// identifies an edge in the metagraph; parent lookups now live on `HSGraph` (see
+/// `node_parents`/`edge_parents`) since the CSR-backed storage they read from belongs to the
+/// graph, not to individual edges
 pub trait HSEdge {
-    fn parents(&self) -> &[EdgeId];
     fn id(&self) -> EdgeId;
 }
 
@@ -177,6 +189,66 @@ pub trait StoreableGraph {
     fn from_file_binary(filename: &str) -> Result<Self, bincode::Error>
     where
         Self: Sized;
+
+    /// like `to_file_binary`, but also records a length+mtime fingerprint of `source_file` in a
+    /// sidecar `<filename>.fingerprint` file, so a later `from_file_binary_checked` call can
+    /// tell whether `source_file` has changed since the cache was written
+    fn to_file_binary_checked(
+        &self,
+        filename: &str,
+        source_file: &str,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        Self: Serialize,
+    {
+        self.to_file_binary(filename)?;
+        std::fs::write(
+            fingerprint_path(filename),
+            source_fingerprint(source_file)?.to_le_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// loads a cache previously written by `to_file_binary_checked`, but only if its sidecar
+    /// fingerprint still matches `source_file`'s current length and mtime; returns an error otherwise so
+    /// the caller can fall back to rebuilding the cache from `source_file`
+    fn from_file_binary_checked(
+        filename: &str,
+        source_file: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        Self: Sized,
+    {
+        let stored = std::fs::read(fingerprint_path(filename))?;
+        let stored: [u8; 8] = stored
+            .as_slice()
+            .try_into()
+            .map_err(|_| "corrupt cache fingerprint file")?;
+        if u64::from_le_bytes(stored) != source_fingerprint(source_file)? {
+            return Err("cache is stale: source file fingerprint mismatch".into());
+        }
+        Ok(Self::from_file_binary(filename)?)
+    }
+}
+
+fn fingerprint_path(filename: &str) -> String {
+    [filename, ".fingerprint"].concat()
+}
+
+/// cheap fingerprint of a source file's content: hashes its length and modification time rather
+/// than streaming the whole file, which is enough to catch edits/replacements without paying the
+/// cost of rereading a multi-gigabyte `.fmi` file on every cache hit
+fn source_fingerprint(filename: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    use std::hash::{Hash, Hasher};
+    let meta = std::fs::metadata(filename)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    meta.len().hash(&mut hasher);
+    meta.modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .hash(&mut hasher);
+    Ok(hasher.finish())
 }
 
 pub trait CHGraph: BaseGraph {
@@ -190,6 +262,11 @@ pub trait CHGraph: BaseGraph {
 pub trait HSGraph: BaseGraph {
     fn toporder(&self, edge_id: EdgeId) -> usize;
     fn iter_edges_topordered(&self) -> std::slice::Iter<Self::Edge>;
+    /// base edges that have `id` as their source or target (the old `HSNode::parents`, moved
+    /// here so implementors can back it with a flat CSR array instead of a per-node `Vec`)
+    fn node_parents(&self, id: NodeId) -> &[EdgeId];
+    /// CH edges whose shortcut DAG has `id` as a direct child (the old `HSEdge::parents`)
+    fn edge_parents(&self, id: EdgeId) -> &[EdgeId];
 }
 
 pub trait GeoGraph: BaseGraph {
@@ -348,3 +348,121 @@ impl StoreableGraph for AdjArrayGraph {
         bincode::deserialize_from(reader)
     }
 }
+
+/// bit-packed integers, each stored in the minimum number of bits that fit the largest value in
+/// the slice, instead of a full `usize`/`EdgeId` per entry. `size()` shows `offsets_out`/
+/// `offsets_in`/`edges_out`/`edges_in` dominate an `AdjArrayGraph`'s memory footprint, and every
+/// entry in them is bounded by `num_edges()` or `num_nodes()` - far fewer bits than a 64-bit word
+/// on any graph smaller than a few billion edges.
+#[derive(Serialize, Deserialize)]
+struct PackedInts {
+    bits: u32,
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl PackedInts {
+    fn pack(values: impl Iterator<Item = usize> + Clone) -> Self {
+        let max = values.clone().max().unwrap_or(0);
+        let bits = bits_needed(max);
+        let len = values.clone().count();
+        let mut words = vec![0u64; (len * bits as usize + 63) / 64];
+        for (i, v) in values.enumerate() {
+            set_bits(&mut words, i * bits as usize, bits, v as u64);
+        }
+        Self { bits, len, words }
+    }
+
+    fn get(&self, i: usize) -> usize {
+        get_bits(&self.words, i * self.bits as usize, self.bits) as usize
+    }
+}
+
+/// number of bits needed to represent every value in `0..=max`
+fn bits_needed(max: usize) -> u32 {
+    (usize::BITS - max.leading_zeros()).max(1)
+}
+
+fn get_bits(words: &[u64], bit_offset: usize, bits: u32) -> u64 {
+    let word_idx = bit_offset / 64;
+    let bit_idx = bit_offset % 64;
+    let mut value = words[word_idx] >> bit_idx;
+    let read_in_word = 64 - bit_idx as u32;
+    if read_in_word < bits {
+        value |= words[word_idx + 1] << read_in_word;
+    }
+    let mask = if bits == 64 { u64::MAX } else { (1u64 << bits) - 1 };
+    value & mask
+}
+
+fn set_bits(words: &mut [u64], bit_offset: usize, bits: u32, value: u64) {
+    let word_idx = bit_offset / 64;
+    let bit_idx = bit_offset % 64;
+    let mask = if bits == 64 { u64::MAX } else { (1u64 << bits) - 1 };
+    let value = value & mask;
+    words[word_idx] |= value << bit_idx;
+    let written_in_word = 64 - bit_idx as u32;
+    if written_in_word < bits {
+        words[word_idx + 1] |= value >> written_in_word;
+    }
+}
+
+/// on-disk layout for `AdjArrayGraph::to_file_binary_packed`: `nodes`/`edges` are stored as-is,
+/// but the four CSR arrays are bit-packed via `PackedInts` instead of `Vec<usize>`/`Vec<EdgeId>`.
+#[derive(Serialize, Deserialize)]
+struct PackedAdjArrayGraph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    offsets_out: PackedInts,
+    offsets_in: PackedInts,
+    edges_out: PackedInts,
+    edges_in: PackedInts,
+}
+
+impl AdjArrayGraph {
+    /// like `to_file_binary`, but bit-packs the CSR arrays (see `PackedAdjArrayGraph`) instead of
+    /// storing them as full-width integers
+    pub fn to_file_binary_packed(&self, filename: &str) -> Result<(), bincode::Error> {
+        let packed = PackedAdjArrayGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|n| Node { id: n.id, lat: n.lat, lon: n.lon, level: n.level })
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .map(|e| Edge {
+                    source: e.source,
+                    target: e.target,
+                    cost: e.cost,
+                    child1: e.child1,
+                    child2: e.child2,
+                })
+                .collect(),
+            offsets_out: PackedInts::pack(self.offsets_out.iter().copied()),
+            offsets_in: PackedInts::pack(self.offsets_in.iter().copied()),
+            edges_out: PackedInts::pack(self.edges_out.iter().map(|e| e.0)),
+            edges_in: PackedInts::pack(self.edges_in.iter().map(|e| e.0)),
+        };
+        let file = std::fs::File::create(filename)?;
+        let mut writer = std::io::BufWriter::new(file);
+        bincode::serialize_into(&mut writer, &packed)
+    }
+
+    /// loads a file previously written by `to_file_binary_packed`
+    pub fn from_file_binary_packed(filename: &str) -> Result<Self, bincode::Error> {
+        let file = std::fs::File::open(filename)?;
+        let reader = std::io::BufReader::new(file);
+        let packed: PackedAdjArrayGraph = bincode::deserialize_from(reader)?;
+
+        Ok(Self {
+            nodes: packed.nodes,
+            edges: packed.edges,
+            offsets_out: (0..packed.offsets_out.len).map(|i| packed.offsets_out.get(i)).collect(),
+            offsets_in: (0..packed.offsets_in.len).map(|i| packed.offsets_in.get(i)).collect(),
+            edges_out: (0..packed.edges_out.len).map(|i| packed.edges_out.get(i).into()).collect(),
+            edges_in: (0..packed.edges_in.len).map(|i| packed.edges_in.get(i).into()).collect(),
+        })
+    }
+}
@@ -3,16 +3,15 @@ use std::convert::TryFrom;
 
 use super::{
     fmigraph::{FMIEdge, FMIGraph, FMINode},
-    BaseEdge, BaseGraph, BaseNode, ChildEdge, EdgeId, HSEdge, HSGraph, HSNode, NodeId,
-    StoreableGraph,
+    BaseEdge, BaseGraph, BaseNode, ChildEdge, EdgeId, HSEdge, HSGraph, NodeId, StoreableGraph,
 };
+use crate::paths::{CHEdgeList, EdgeList};
 
 // define Node, Edge, Graph for this HSGraph
 
 #[derive(Serialize, Deserialize)]
 pub struct Node {
     pub id: NodeId,
-    pub parents: Vec<EdgeId>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -22,7 +21,6 @@ pub struct Edge {
     pub target: NodeId,
     pub child1: Option<EdgeId>,
     pub child2: Option<EdgeId>,
-    pub parents: Vec<EdgeId>, // for DAG of edge replacements
 }
 
 #[derive(Serialize, Deserialize)]
@@ -30,6 +28,15 @@ pub struct ToporderedGraph {
     nodes: Vec<Node>,
     edges: Vec<Edge>,        // sorted by toporder
     edge_id_map: Vec<usize>, // map: EdgeId -> index into edges
+    // CSR storage for the metagraph's parent relations (see HSGraph::node_parents/edge_parents):
+    // node_parent_edges[node_parent_offsets[n]..node_parent_offsets[n + 1]] are the base edges
+    // touching node n, edge_parent_edges[edge_parent_offsets[e]..edge_parent_offsets[e + 1]] are
+    // the CH edges that have e as a direct child. both are keyed by the edges' own ids, not by
+    // their position in `edges` (which is toporder, not id order).
+    node_parent_edges: Vec<EdgeId>,
+    node_parent_offsets: Vec<usize>,
+    edge_parent_edges: Vec<EdgeId>,
+    edge_parent_offsets: Vec<usize>,
 }
 
 // impl traits
@@ -40,18 +47,9 @@ impl BaseNode for Node {
     }
 }
 
-impl HSNode for Node {
-    fn parents(&self) -> &[EdgeId] {
-        &self.parents
-    }
-}
-
 impl From<FMINode> for Node {
     fn from(n: FMINode) -> Self {
-        Self {
-            id: n.id.into(),
-            parents: Vec::new(),
-        }
+        Self { id: n.id.into() }
     }
 }
 
@@ -65,9 +63,6 @@ impl BaseEdge for Edge {
 }
 
 impl HSEdge for Edge {
-    fn parents(&self) -> &[EdgeId] {
-        &self.parents
-    }
     fn id(&self) -> EdgeId {
         self.id
     }
@@ -96,7 +91,6 @@ impl From<FMIEdge> for Edge {
                 Ok(c) => Some(c.into()),
                 Err(_) => None,
             },
-            parents: Vec::new(),
         }
     }
 }
@@ -115,26 +109,26 @@ impl From<FMIGraph> for ToporderedGraph {
         // add an index to all edges before reordering
         let mut edges_with_index: Vec<(usize, Edge)> = edges.into_iter().enumerate().collect();
 
-        // calculate parents data for each edge
-        let mut parents: Vec<Vec<EdgeId>> = vec![Vec::new(); edges_with_index.len()];
+        // calculate parents data for each edge and each node, keyed by their own (pre-toporder)
+        // id rather than by position in edges_with_index - flattened into CSR storage below
+        let mut node_parents: Vec<Vec<EdgeId>> = vec![Vec::new(); nodes.len()];
+        let mut edge_parents: Vec<Vec<EdgeId>> = vec![Vec::new(); edges_with_index.len()];
 
         for (edgeid, edge) in &edges_with_index {
             let edgeid: EdgeId = (*edgeid).into();
             if let (Some(c1), Some(c2)) = (edge.child1, edge.child2) {
                 // edge is ch edge. add edge to children's parents list
-                parents[c1].push(edgeid);
-                parents[c2].push(edgeid);
+                edge_parents[c1].push(edgeid);
+                edge_parents[c2].push(edgeid);
             } else {
                 // edge is base edge. add edge to its nodes parents list
-                nodes[edge.source].parents.push(edgeid);
-                nodes[edge.target].parents.push(edgeid);
+                node_parents[edge.source].push(edgeid);
+                node_parents[edge.target].push(edgeid);
             }
         }
 
-        // store parents in the edge struct
-        for (id, parent) in parents.into_iter().enumerate() {
-            edges_with_index[id].1.parents = parent;
-        }
+        let (node_parent_edges, node_parent_offsets) = flatten_csr(node_parents);
+        let (edge_parent_edges, edge_parent_offsets) = flatten_csr(edge_parents);
 
         // topsort edges array & calculate edge_id_map
         let topsorted = edge_top_sort(&edges_with_index);
@@ -160,48 +154,81 @@ impl From<FMIGraph> for ToporderedGraph {
             nodes: nodes,
             edge_id_map,
             edges: edges_with_index.into_iter().map(|(_, e)| e).collect(),
+            node_parent_edges,
+            node_parent_offsets,
+            edge_parent_edges,
+            edge_parent_offsets,
         }
     }
 }
 
-/// sorts edges in topological order
-/// impl taken from https://www.geeksforgeeks.org/topological-sorting/
+/// flattens a ragged `id -> Vec<EdgeId>` mapping into CSR layout: a single contiguous data array
+/// plus an offsets array of length `lists.len() + 1`, where `data[offsets[i]..offsets[i + 1]]` is
+/// the list that belonged to `i`.
+fn flatten_csr(lists: Vec<Vec<EdgeId>>) -> (Vec<EdgeId>, Vec<usize>) {
+    let mut offsets = Vec::with_capacity(lists.len() + 1);
+    let mut data = Vec::with_capacity(lists.iter().map(Vec::len).sum());
+    offsets.push(0);
+    for list in lists {
+        data.extend(list);
+        offsets.push(data.len());
+    }
+    (data, offsets)
+}
+
+/// sorts edges in topological order. drives an explicit-stack post-order DFS over `child1`/
+/// `child2` (see `top_sort_iter`) instead of recursing, since the shortcut DAG of a real
+/// contraction hierarchy can be deep enough to blow the thread stack with a recursive walk.
 fn edge_top_sort(edges_with_index: &Vec<(usize, Edge)>) -> Vec<EdgeId> {
     let mut visited = vec![false; edges_with_index.len()];
     let mut res = Vec::new();
 
     for (id, _) in edges_with_index {
         if !visited[*id] {
-            let mut sub_res = top_sort_rec((*id).into(), &mut visited, edges_with_index);
-            res.append(&mut sub_res);
+            top_sort_iter((*id).into(), &mut visited, edges_with_index, &mut res);
         }
     }
     res.reverse();
     res
 }
 
-/// internal function for topological sorting - see edge_top_sort
-fn top_sort_rec(
-    id: EdgeId,
-    mut visited: &mut Vec<bool>,
+/// post-order DFS over the shortcut DAG rooted at `start`, appending every newly-visited edge to
+/// `res` as it's finished. each stack frame tracks a cursor (0 = children not yet looked at, 1 =
+/// `child1` handled, 2 = both children handled) so a frame can be revisited after its child's
+/// subtree drains instead of recursing into it.
+fn top_sort_iter(
+    start: EdgeId,
+    visited: &mut Vec<bool>,
     edges_with_index: &Vec<(usize, Edge)>,
-) -> Vec<EdgeId> {
-    visited[id] = true;
-    let mut res = Vec::new();
-
-    let (index, edge) = &edges_with_index[id.0];
-    assert_eq!(*index, id.0);
-    if let (Some(c1), Some(c2)) = (edge.child1, edge.child2) {
-        if !visited[c1] {
-            res.append(&mut top_sort_rec(c1, &mut visited, edges_with_index));
-        }
-        if !visited[c2] {
-            res.append(&mut top_sort_rec(c2, &mut visited, edges_with_index));
+    res: &mut Vec<EdgeId>,
+) {
+    let mut stack: Vec<(EdgeId, u8)> = vec![(start, 0)];
+    visited[start] = true;
+
+    while let Some(&mut (id, cursor)) = stack.last_mut() {
+        let (index, edge) = &edges_with_index[id.0];
+        assert_eq!(*index, id.0);
+
+        let next_child = match (edge.child1, edge.child2, cursor) {
+            (Some(c1), Some(_), 0) => Some(c1),
+            (Some(_), Some(c2), 1) => Some(c2),
+            _ => None,
+        };
+
+        match next_child {
+            Some(child) => {
+                stack.last_mut().unwrap().1 = cursor + 1;
+                if !visited[child] {
+                    visited[child] = true;
+                    stack.push((child, 0));
+                }
+            }
+            None => {
+                res.push(id);
+                stack.pop();
+            }
         }
     }
-
-    res.push(id);
-    res
 }
 
 impl BaseGraph for ToporderedGraph {
@@ -227,6 +254,10 @@ impl BaseGraph for ToporderedGraph {
             std::mem::size_of_val(&*self.nodes)
                 + std::mem::size_of_val(&*self.edges)
                 + std::mem::size_of_val(&*self.edge_id_map)
+                + std::mem::size_of_val(&*self.node_parent_edges)
+                + std::mem::size_of_val(&*self.node_parent_offsets)
+                + std::mem::size_of_val(&*self.edge_parent_edges)
+                + std::mem::size_of_val(&*self.edge_parent_offsets)
         );
     }
 
@@ -254,11 +285,45 @@ impl HSGraph for ToporderedGraph {
     fn toporder(&self, edge_id: EdgeId) -> usize {
         self.edge_id_map[edge_id]
     }
+    fn node_parents(&self, id: NodeId) -> &[EdgeId] {
+        &self.node_parent_edges
+            [self.node_parent_offsets[id]..self.node_parent_offsets[id + 1.into()]]
+    }
+    fn edge_parents(&self, id: EdgeId) -> &[EdgeId] {
+        &self.edge_parent_edges
+            [self.edge_parent_offsets[id]..self.edge_parent_offsets[id + 1.into()]]
+    }
     fn iter_edges_topordered(&self) -> std::slice::Iter<Self::Edge> {
         self.edges.iter()
     }
 }
 
+impl ToporderedGraph {
+    /// expands every (possibly shortcut) edge in `edges` into the base edges it stands for,
+    /// preserving left-to-right order so the result is a contiguous path ready for
+    /// `VisBuilder::path`. a shortcut edge is replaced by `unpack(child1)` followed by
+    /// `unpack(child2)`; a base edge is emitted as-is. uses an explicit stack (pushing `child2`
+    /// before `child1` so `child1`'s whole subtree drains first) instead of recursing, since CH
+    /// shortcut DAGs can be too deep for the thread stack.
+    pub fn unpack(&self, edges: &CHEdgeList) -> EdgeList {
+        let mut result = Vec::new();
+        let mut stack: Vec<EdgeId> = edges.list.iter().rev().copied().collect();
+
+        while let Some(id) = stack.pop() {
+            let edge = self.edge(id);
+            match (edge.child1, edge.child2) {
+                (Some(c1), Some(c2)) => {
+                    stack.push(c2);
+                    stack.push(c1);
+                }
+                _ => result.push(id),
+            }
+        }
+
+        EdgeList(result)
+    }
+}
+
 impl StoreableGraph for ToporderedGraph {
     fn from_file_binary(filename: &str) -> Result<Self, bincode::Error> {
         let file = std::fs::File::open(filename)?;
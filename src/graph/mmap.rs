@@ -0,0 +1,191 @@
+//! zero-copy, memory-mapped alternative to `FMIGraph`'s eager bincode loading. instead of
+//! deserializing the whole graph into `Vec<FMINode>`/`Vec<FMIEdge>`, `MmapFMIGraph` mmaps a
+//! fixed-layout file and serves `BaseGraph`/`GeoGraph` accessors directly out of the mapping,
+//! so opening a graph that doesn't fit in RAM only costs address space, not memory.
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::mem::size_of;
+
+use memmap2::Mmap;
+
+use super::{BaseEdge, BaseGraph, BaseNode, CHNode, EdgeId, FMIGraph, GeoGraph, GeoNode, NodeId};
+
+/// on-disk, fixed-layout mirror of `FMINode`. `repr(C)` and 8-byte aligned so it can be cast
+/// directly out of a byte slice without copying.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct RawFMINode {
+    pub id: u64,
+    pub lat: f64,
+    pub lon: f64,
+    pub level: u32,
+    _pad: u32,
+}
+
+/// on-disk, fixed-layout mirror of `FMIEdge`
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct RawFMIEdge {
+    pub id: u64,
+    pub source: u64,
+    pub target: u64,
+    pub cost: u32,
+    pub child1: i32,
+    pub child2: i32,
+    _pad: u32,
+}
+
+impl BaseNode for RawFMINode {
+    fn id(&self) -> NodeId {
+        (self.id as usize).into()
+    }
+}
+
+impl GeoNode for RawFMINode {
+    fn lat(&self) -> f64 {
+        self.lat
+    }
+    fn lon(&self) -> f64 {
+        self.lon
+    }
+}
+
+// the quadtree's `TreeNode` bound requires `CHNode`; the mmap format isn't a `CHGraph`, but every
+// node record keeps the CH level it was written with, and that stored level is reported here.
+impl CHNode for RawFMINode {
+    fn level(&self) -> u32 {
+        self.level
+    }
+}
+
+impl BaseEdge for RawFMIEdge {
+    fn source(&self) -> NodeId {
+        (self.source as usize).into()
+    }
+    fn target(&self) -> NodeId {
+        (self.target as usize).into()
+    }
+}
+
+const HEADER_LEN: usize = 2 * size_of::<u64>();
+
+/// a `FMIGraph` lent out of a memory-mapped file instead of held as owned `Vec`s.
+/// built with `FMIGraph::to_file_mmap` and opened with `MmapFMIGraph::open`.
+pub struct MmapFMIGraph {
+    mmap: Mmap,
+    num_nodes: usize,
+    num_edges: usize,
+    nodes_offset: usize,
+    edges_offset: usize,
+}
+
+impl MmapFMIGraph {
+    /// memory-maps `filename`, which must have been written by `FMIGraph::to_file_mmap`
+    pub fn open(filename: &str) -> std::io::Result<Self> {
+        let file = File::open(filename)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let num_nodes = u64::from_le_bytes(mmap[0..8].try_into().unwrap()) as usize;
+        let num_edges = u64::from_le_bytes(mmap[8..16].try_into().unwrap()) as usize;
+        let nodes_offset = HEADER_LEN;
+        let edges_offset = nodes_offset + num_nodes * size_of::<RawFMINode>();
+
+        Ok(Self {
+            mmap,
+            num_nodes,
+            num_edges,
+            nodes_offset,
+            edges_offset,
+        })
+    }
+
+    fn nodes(&self) -> &[RawFMINode] {
+        let bytes = &self.mmap[self.nodes_offset..self.nodes_offset + self.num_nodes * size_of::<RawFMINode>()];
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const RawFMINode, self.num_nodes) }
+    }
+
+    fn edges(&self) -> &[RawFMIEdge] {
+        let bytes = &self.mmap[self.edges_offset..self.edges_offset + self.num_edges * size_of::<RawFMIEdge>()];
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const RawFMIEdge, self.num_edges) }
+    }
+}
+
+impl BaseGraph for MmapFMIGraph {
+    type Node = RawFMINode;
+    type Edge = RawFMIEdge;
+
+    fn size(&self) {
+        println!(
+            "#Nodes: {}\t#Edges: {}\tmapped: {} bytes",
+            self.num_nodes,
+            self.num_edges,
+            self.mmap.len()
+        );
+    }
+
+    fn edge(&self, id: EdgeId) -> &Self::Edge {
+        &self.edges()[id.0]
+    }
+    fn node(&self, id: NodeId) -> &Self::Node {
+        &self.nodes()[id.0]
+    }
+    fn iter_nodes(&self) -> std::slice::Iter<'_, Self::Node> {
+        self.nodes().iter()
+    }
+    fn iter_edges(&self) -> std::slice::Iter<'_, Self::Edge> {
+        self.edges().iter()
+    }
+    fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+    fn num_edges(&self) -> usize {
+        self.num_edges
+    }
+}
+
+impl GeoGraph for MmapFMIGraph {
+    fn node(&self, id: NodeId) -> &dyn GeoNode {
+        &self.nodes()[id.0]
+    }
+}
+
+impl FMIGraph {
+    /// writes this graph in the fixed-layout format `MmapFMIGraph` expects: a small header
+    /// giving the node/edge counts, followed by the node array, followed by the edge array -
+    /// each array `repr(C)` and 8-byte aligned so it can be cast out of the mapping as-is.
+    pub fn to_file_mmap(&self, filename: &str) -> std::io::Result<()> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+
+        writer.write_all(&(self.nodes.len() as u64).to_le_bytes())?;
+        writer.write_all(&(self.edges.len() as u64).to_le_bytes())?;
+
+        for n in &self.nodes {
+            let raw = RawFMINode {
+                id: n.id as u64,
+                lat: n.lat,
+                lon: n.lon,
+                level: n.level,
+                _pad: 0,
+            };
+            writer.write_all(raw_bytes(&raw))?;
+        }
+        for e in &self.edges {
+            let raw = RawFMIEdge {
+                id: e.id as u64,
+                source: e.source as u64,
+                target: e.target as u64,
+                cost: e.cost,
+                child1: e.child1,
+                child2: e.child2,
+                _pad: 0,
+            };
+            writer.write_all(raw_bytes(&raw))?;
+        }
+        writer.flush()
+    }
+}
+
+fn raw_bytes<T: Copy>(value: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
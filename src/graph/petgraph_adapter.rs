@@ -0,0 +1,295 @@
+//! implements the de-facto petgraph visitor interface on top of `ToporderedGraph`'s existing
+//! `BaseGraph`/`HSGraph` accessors, so generic algorithms from the petgraph ecosystem (connected
+//! components, BFS/DFS, isomorphism checks, ...) can run directly against a loaded metagraph
+//! without copying it into a `petgraph::Graph`. traversal only ever surfaces base (non-shortcut)
+//! edges - `node_parents` already only collects those - so callers see the original road network,
+//! not the CH metagraph. all impls are on `&'a ToporderedGraph` rather than `ToporderedGraph`
+//! itself, matching how petgraph implements these traits for its own graph types.
+
+use petgraph::visit::{
+    Data, EdgeCount, EdgeRef, GraphBase, IntoEdgeReferences, IntoEdges, IntoNeighbors, NodeCount,
+    NodeIndexable, VisitMap, Visitable,
+};
+
+use super::{BaseGraph, EdgeId, HSGraph, NodeId, ToporderedGraph};
+
+/// a base edge, referenced by its endpoints and id - the `petgraph::visit::EdgeRef` this adapter
+/// hands out. there's no weight to report (the base graph stores no cost), so `Weight` is `()`.
+#[derive(Copy, Clone)]
+pub struct HSEdgeRef {
+    source: NodeId,
+    target: NodeId,
+    id: EdgeId,
+}
+
+impl EdgeRef for HSEdgeRef {
+    type NodeId = NodeId;
+    type EdgeId = EdgeId;
+    type Weight = ();
+
+    fn source(&self) -> NodeId {
+        self.source
+    }
+    fn target(&self) -> NodeId {
+        self.target
+    }
+    fn weight(&self) -> &() {
+        &()
+    }
+    fn id(&self) -> EdgeId {
+        self.id
+    }
+}
+
+impl<'a> GraphBase for &'a ToporderedGraph {
+    type NodeId = NodeId;
+    type EdgeId = EdgeId;
+}
+
+impl<'a> Data for &'a ToporderedGraph {
+    type NodeWeight = ();
+    type EdgeWeight = ();
+}
+
+impl<'a> NodeCount for &'a ToporderedGraph {
+    fn node_count(&self) -> usize {
+        self.num_nodes()
+    }
+}
+
+impl<'a> EdgeCount for &'a ToporderedGraph {
+    fn edge_count(&self) -> usize {
+        self.iter_edges()
+            .filter(|e| e.child1.is_none() && e.child2.is_none())
+            .count()
+    }
+}
+
+impl<'a> NodeIndexable for &'a ToporderedGraph {
+    fn node_bound(&self) -> usize {
+        self.num_nodes()
+    }
+    fn to_index(&self, a: NodeId) -> usize {
+        a.0
+    }
+    fn from_index(&self, i: usize) -> NodeId {
+        i.into()
+    }
+}
+
+impl<'a> IntoNeighbors for &'a ToporderedGraph {
+    type Neighbors = Box<dyn Iterator<Item = NodeId> + 'a>;
+
+    fn neighbors(self, a: NodeId) -> Self::Neighbors {
+        Box::new(self.node_parents(a).iter().map(move |&e| {
+            let edge = self.edge(e);
+            if edge.source == a {
+                edge.target
+            } else {
+                edge.source
+            }
+        }))
+    }
+}
+
+impl<'a> IntoEdgeReferences for &'a ToporderedGraph {
+    type EdgeRef = HSEdgeRef;
+    type EdgeReferences = Box<dyn Iterator<Item = HSEdgeRef> + 'a>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        Box::new(
+            self.iter_edges()
+                .filter(|e| e.child1.is_none() && e.child2.is_none())
+                .map(|e| HSEdgeRef {
+                    source: e.source,
+                    target: e.target,
+                    id: e.id,
+                }),
+        )
+    }
+}
+
+impl<'a> IntoEdges for &'a ToporderedGraph {
+    type Edges = Box<dyn Iterator<Item = HSEdgeRef> + 'a>;
+
+    fn edges(self, a: NodeId) -> Self::Edges {
+        Box::new(self.node_parents(a).iter().map(move |&e| {
+            let edge = self.edge(e);
+            HSEdgeRef {
+                source: edge.source,
+                target: edge.target,
+                id: edge.id,
+            }
+        }))
+    }
+}
+
+/// a plain `Vec<bool>` visited-set, indexed by `NodeId`. implementing `VisitMap<NodeId>` for a
+/// foreign type (`Vec`) on behalf of a foreign trait (petgraph's) is allowed here because `NodeId`
+/// - the trait's generic parameter - is local to this crate, which is all the orphan rule needs.
+impl VisitMap<NodeId> for Vec<bool> {
+    fn visit(&mut self, a: NodeId) -> bool {
+        let was_visited = self[a.0];
+        self[a.0] = true;
+        !was_visited
+    }
+    fn is_visited(&self, a: &NodeId) -> bool {
+        self[a.0]
+    }
+}
+
+impl<'a> Visitable for &'a ToporderedGraph {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Vec<bool> {
+        vec![false; self.num_nodes()]
+    }
+    fn reset_map(&self, map: &mut Vec<bool>) {
+        map.clear();
+        map.resize(self.num_nodes(), false);
+    }
+}
+
+/// same idea as the `ToporderedGraph` adapter above, but for `AdjArrayGraph` - gated behind the
+/// `petgraph-interop` feature since, unlike the metagraph adapter, most callers never need it.
+/// `AdjArrayGraph` is already CSR-stored (`offsets_out`/`edges_out`), so `out_edges(n, Both)`
+/// *is* the CSR slice for `n` and every impl here is a thin wrapper around the crate's own
+/// `CHGraph` accessors - traversal sees CH shortcuts as ordinary edges, same as `out_edges` does.
+#[cfg(feature = "petgraph-interop")]
+mod adj_array {
+    use petgraph::visit::{
+        Data, EdgeCount, EdgeRef, GraphBase, IntoEdgeReferences, IntoEdges, IntoNeighbors,
+        IntoNodeIdentifiers, NodeCount, NodeIndexable, Visitable,
+    };
+
+    use crate::graph::{AdjArrayGraph, BaseEdge, BaseGraph, CHDirection, CHGraph, CostEdge, EdgeId, NodeId};
+
+    /// a CH edge, referenced by its endpoints, id, and cost - the `petgraph::visit::EdgeRef` this
+    /// adapter hands out. unlike `HSEdgeRef` (the metagraph's base-edge-only view), this surfaces
+    /// every CSR edge including shortcuts, matching what `out_edges(_, CHDirection::Both)` returns.
+    #[derive(Copy, Clone)]
+    pub struct CHEdgeRef {
+        source: NodeId,
+        target: NodeId,
+        id: EdgeId,
+        weight: u32,
+    }
+
+    impl EdgeRef for CHEdgeRef {
+        type NodeId = NodeId;
+        type EdgeId = EdgeId;
+        type Weight = u32;
+
+        fn source(&self) -> NodeId {
+            self.source
+        }
+        fn target(&self) -> NodeId {
+            self.target
+        }
+        fn weight(&self) -> &u32 {
+            &self.weight
+        }
+        fn id(&self) -> EdgeId {
+            self.id
+        }
+    }
+
+    impl<'a> GraphBase for &'a AdjArrayGraph {
+        type NodeId = NodeId;
+        type EdgeId = EdgeId;
+    }
+
+    impl<'a> Data for &'a AdjArrayGraph {
+        type NodeWeight = ();
+        type EdgeWeight = u32;
+    }
+
+    impl<'a> NodeCount for &'a AdjArrayGraph {
+        fn node_count(&self) -> usize {
+            self.num_nodes()
+        }
+    }
+
+    impl<'a> EdgeCount for &'a AdjArrayGraph {
+        fn edge_count(&self) -> usize {
+            self.num_edges()
+        }
+    }
+
+    impl<'a> NodeIndexable for &'a AdjArrayGraph {
+        fn node_bound(&self) -> usize {
+            self.num_nodes()
+        }
+        fn to_index(&self, a: NodeId) -> usize {
+            a.0
+        }
+        fn from_index(&self, i: usize) -> NodeId {
+            i.into()
+        }
+    }
+
+    impl<'a> IntoNeighbors for &'a AdjArrayGraph {
+        type Neighbors = Box<dyn Iterator<Item = NodeId> + 'a>;
+
+        fn neighbors(self, a: NodeId) -> Self::Neighbors {
+            Box::new(
+                self.out_edges(a, CHDirection::Both)
+                    .iter()
+                    .map(move |&e| self.edge(e).target()),
+            )
+        }
+    }
+
+    impl<'a> IntoEdges for &'a AdjArrayGraph {
+        type Edges = Box<dyn Iterator<Item = CHEdgeRef> + 'a>;
+
+        fn edges(self, a: NodeId) -> Self::Edges {
+            Box::new(self.out_edges(a, CHDirection::Both).iter().map(move |&e| {
+                let edge = self.edge(e);
+                CHEdgeRef {
+                    source: edge.source(),
+                    target: edge.target(),
+                    id: e,
+                    weight: edge.cost(),
+                }
+            }))
+        }
+    }
+
+    impl<'a> IntoEdgeReferences for &'a AdjArrayGraph {
+        type EdgeRef = CHEdgeRef;
+        type EdgeReferences = Box<dyn Iterator<Item = CHEdgeRef> + 'a>;
+
+        fn edge_references(self) -> Self::EdgeReferences {
+            Box::new(self.iter_edges().enumerate().map(|(i, edge)| CHEdgeRef {
+                source: edge.source(),
+                target: edge.target(),
+                id: i.into(),
+                weight: edge.cost(),
+            }))
+        }
+    }
+
+    impl<'a> IntoNodeIdentifiers for &'a AdjArrayGraph {
+        type NodeIdentifiers = Box<dyn Iterator<Item = NodeId> + 'a>;
+
+        fn node_identifiers(self) -> Self::NodeIdentifiers {
+            Box::new((0..self.num_nodes()).map(NodeId::from))
+        }
+    }
+
+    impl<'a> Visitable for &'a AdjArrayGraph {
+        type Map = Vec<bool>;
+
+        fn visit_map(&self) -> Vec<bool> {
+            vec![false; self.num_nodes()]
+        }
+        fn reset_map(&self, map: &mut Vec<bool>) {
+            map.clear();
+            map.resize(self.num_nodes(), false);
+        }
+    }
+}
+
+#[cfg(feature = "petgraph-interop")]
+pub use adj_array::CHEdgeRef;
@@ -0,0 +1,87 @@
+//! loads an `FMIGraph` straight out of a PostgreSQL/PostGIS schema instead of a flat `.fmi`
+//! file, for routing deployments whose topology already lives in a spatial database. gated
+//! behind the `postgis` feature since it pulls in the `postgres` and `geo-types` crates, which
+//! most users of this crate don't need.
+use geo_types::Geometry;
+use geozero::wkb::Decode;
+use postgres::{Client, NoTls};
+
+use super::{FMIEdge, FMIGraph, FMINode};
+
+/// connection and schema info for [`FMIGraph::from_postgis`]. the edge table needs source/target
+/// node-id columns, a cost column and is assumed to carry one row per edge; the node table needs
+/// a node-id column and a point geometry column.
+pub struct PostgisConfig<'a> {
+    pub conn_str: &'a str,
+
+    pub node_table: &'a str,
+    pub node_id_column: &'a str,
+    pub node_geom_column: &'a str,
+
+    pub edge_table: &'a str,
+    pub edge_source_column: &'a str,
+    pub edge_target_column: &'a str,
+    pub edge_cost_column: &'a str,
+}
+
+impl FMIGraph {
+    /// builds an `FMIGraph` by streaming a node table and an edge table out of a PostGIS
+    /// database. node geometries are decoded client-side through geozero rather than via
+    /// `ST_X`/`ST_Y`, so any point geometry PostGIS can hand back as WKB works. edges are
+    /// assigned sequential `EdgeId`s in query result order, same as the CSV loaders.
+    pub fn from_postgis(config: &PostgisConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut client = Client::connect(config.conn_str, NoTls)?;
+
+        let node_query = format!(
+            "SELECT {id}, {geom} FROM {table} ORDER BY {id}",
+            id = config.node_id_column,
+            geom = config.node_geom_column,
+            table = config.node_table,
+        );
+        let mut nodes = Vec::new();
+        for row in client.query(&node_query, &[])? {
+            let id: i64 = row.get(0);
+            let geom: Decode<Geometry<f64>> = row.get(1);
+            let (lon, lat) = match geom.geometry {
+                Some(Geometry::Point(p)) => (p.x(), p.y()),
+                _ => {
+                    return Err(format!(
+                        "node {} in {}: {} did not decode to a point geometry",
+                        id, config.node_table, config.node_geom_column
+                    )
+                    .into())
+                }
+            };
+            nodes.push(FMINode {
+                id: id as usize,
+                lat,
+                lon,
+                level: 0,
+            });
+        }
+
+        let edge_query = format!(
+            "SELECT {source}, {target}, {cost} FROM {table} ORDER BY {source}, {target}",
+            source = config.edge_source_column,
+            target = config.edge_target_column,
+            cost = config.edge_cost_column,
+            table = config.edge_table,
+        );
+        let mut edges = Vec::new();
+        for row in client.query(&edge_query, &[])? {
+            let source: i64 = row.get(0);
+            let target: i64 = row.get(1);
+            let cost: i64 = row.get(2);
+            edges.push(FMIEdge {
+                id: edges.len(),
+                source: source as usize,
+                target: target as usize,
+                cost: cost as u32,
+                child1: -1,
+                child2: -1,
+            });
+        }
+
+        Ok(Self { nodes, edges })
+    }
+}
@@ -1,25 +1,128 @@
-use std::{cmp::Ordering, collections::BinaryHeap, time::Instant};
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    ops::ControlFlow,
+    time::{Duration, Instant},
+};
 
 use rustc_hash::FxHashSet;
 
 use crate::{
-    graph::{BaseEdge, BaseNode, ChildEdge, EdgeId, HSEdge, HSGraph, HSNode, NodeId},
+    dary_heap::DaryHeap,
+    graph::{BaseEdge, BaseNode, ChildEdge, EdgeId, HSEdge, HSGraph, NodeId},
     paths::CHEdgeList,
 };
 
+/// default fanout of the d-ary heap `scan_edges_explore` uses to expand CH edges in topological
+/// order. tune with `new_with_params` to benchmark the effect on the adaptive explorative scans.
+const DEFAULT_EXPLORE_HEAP_D: usize = 4;
+
+/// a maximal run of consecutive base edges that all carry the exact same set of path ids - every
+/// stored path that touches one of them touches all of them. `HittingSet::new` discovers these
+/// up front so `run_path_map` can store the shared path-id set once per run instead of once per
+/// edge; CH edges and base edges that don't chain with a neighbor simply get their own singleton
+/// run. exposed for inspection via `HittingSet::runs`.
+pub struct EdgeRun {
+    pub edges: Vec<EdgeId>,
+}
+
+impl EdgeRun {
+    pub fn first(&self) -> EdgeId {
+        self.edges[0]
+    }
+
+    pub fn last(&self) -> EdgeId {
+        *self.edges.last().unwrap()
+    }
+}
+
+/// decides whether a run-in-progress may be extended across `node`, the shared endpoint between
+/// two consecutive base edges. return `false` to forbid merging across that node, e.g. to keep a
+/// waypoint of interest addressable as its own run.
+pub type RunFilter<'a> = &'a dyn Fn(NodeId) -> bool;
+
+/// finds maximal runs of base edges, in the style of "collect runs" DAG-compression passes: walks
+/// every edge in topological order and greedily extends the run in progress while the next edge
+/// is a base edge, is a direct successor of the previous one (`prev.target() == next.source()`),
+/// carries an identical path-id set in `edge_path_map`, and `filter` allows crossing the shared
+/// node. CH edges, and base edges that don't qualify, start (and end) their own singleton run.
+/// returns the discovered runs plus a total `EdgeId -> run index` map.
+fn collect_runs<N: BaseNode, E: HSEdge + BaseEdge + ChildEdge>(
+    graph: &dyn HSGraph<Node = N, Edge = E>,
+    edge_path_map: &[Vec<usize>],
+    filter: RunFilter,
+) -> (Vec<EdgeRun>, Vec<usize>) {
+    let mut runs: Vec<EdgeRun> = Vec::new();
+    let mut edge_to_run = vec![0usize; graph.num_edges()];
+    let mut current: Option<EdgeId> = None; // last base edge of the run in progress, if any
+
+    for edge in graph.iter_edges_topordered() {
+        let id = edge.id();
+        let is_base = edge.child1().is_none() && edge.child2().is_none();
+
+        let extends_current = is_base
+            && current.map_or(false, |prev| {
+                let prev_edge = graph.edge(prev);
+                prev_edge.target() == edge.source()
+                    && edge_path_map[prev] == edge_path_map[id]
+                    && filter(prev_edge.target())
+            });
+
+        if extends_current {
+            let run = runs.last_mut().unwrap();
+            run.edges.push(id);
+        } else {
+            runs.push(EdgeRun { edges: vec![id] });
+        }
+        edge_to_run[id] = runs.len() - 1;
+        current = is_base.then_some(id);
+    }
+
+    (runs, edge_to_run)
+}
+
+/// per-iteration snapshot passed to `HittingSet::run_with_callback`, so an embedder can drive a
+/// progress bar, a live plot, or a custom stopping rule instead of scraping the `print_stats`
+/// stdout output that `run_with_stats_maxiter` still supports for the CLI binaries.
+pub struct IterationStats {
+    pub iteration: usize,
+    /// the node chosen as this iteration's hitter
+    pub node: NodeId,
+    /// weight-summed paths newly covered by `node`
+    pub weight: u64,
+    /// number of paths newly covered by `node`
+    pub paths_hit: usize,
+    /// paths not yet covered by any hitter, after this iteration
+    pub paths_remaining: usize,
+    /// weight-summed paths not yet covered by any hitter, after this iteration
+    pub weight_remaining: u64,
+    pub elapsed: Duration,
+}
+
 pub struct HittingSet<'a, N, E> {
     hist: Vec<u64>, // current node hist. hist[nodeId] = #occurences
     graph: &'a dyn HSGraph<Node = N, Edge = E>,
-    edge_path_map: Vec<Vec<usize>>, // maps edgeId -> all pathIds where the edge is on the path
+    runs: Vec<EdgeRun>,             // maximal runs of always-co-occurring base edges
+    edge_to_run: Vec<usize>,        // maps edgeId -> index into `runs`
+    run_path_map: Vec<Vec<usize>>,  // maps run index -> all pathIds where the run is on the path
     paths: Vec<CHEdgeList>,
     adaptive_threshold: usize, // this threshold decides if a full scan or an explorative scan is used for a iteration
+    explore_heap_d: usize, // fanout of the d-ary heap used by scan_edges_explore
+    resume: Vec<(NodeId, u64)>, // hitters carried over from a previous, warm-started run
 }
 
-impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a, N, E> {
-    pub fn new_with_threshold(
+impl<'a, N: BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a, N, E> {
+    /// warm-starts from a previously computed (possibly partial) hitting set: every path hit by
+    /// one of `resume`'s nodes is marked satisfied before the greedy loop sees it, so `run`/
+    /// `run_with_stats_maxiter` pick up exactly where the earlier run left off instead of
+    /// recomputing those selections. `resume`'s entries are prepended, unmodified, to the result.
+    pub fn new_with_resume(
         graph: &'a dyn HSGraph<Node = N, Edge = E>,
         paths: Vec<CHEdgeList>,
         adaptive_threshold: usize,
+        explore_heap_d: usize,
+        run_filter: RunFilter,
+        resume: Vec<(NodeId, u64)>,
     ) -> Self {
         // build the edge path map: scan all paths and save them for each edge
         let mut edge_path_map: Vec<Vec<usize>> = vec![Vec::new(); graph.num_edges()];
@@ -29,19 +132,86 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
             }
         }
 
-        Self {
+        // compress maximal runs of always-co-occurring base edges, then collapse edge_path_map
+        // down to one path-id set per run instead of one per edge
+        let (runs, edge_to_run) = collect_runs(graph, &edge_path_map, run_filter);
+        let run_path_map: Vec<Vec<usize>> = runs
+            .iter()
+            .map(|run| edge_path_map[run.first()].clone())
+            .collect();
+
+        let mut hs = Self {
             hist: vec![0; graph.num_nodes()],
             graph: graph.into(),
-            edge_path_map,
+            runs,
+            edge_to_run,
+            run_path_map,
             paths,
             adaptive_threshold,
+            explore_heap_d,
+            resume: Vec::new(),
+        };
+
+        // mark every path already hit by a resumed node as satisfied, same as the greedy loop
+        // would after selecting that node
+        for &(hitter, _) in &resume {
+            for i in hs.hit_paths(hitter) {
+                hs.paths[i].clear();
+            }
         }
+        hs.resume = resume;
+        hs
+    }
+
+    pub fn new_with_run_filter(
+        graph: &'a dyn HSGraph<Node = N, Edge = E>,
+        paths: Vec<CHEdgeList>,
+        adaptive_threshold: usize,
+        explore_heap_d: usize,
+        run_filter: RunFilter,
+    ) -> Self {
+        Self::new_with_resume(
+            graph,
+            paths,
+            adaptive_threshold,
+            explore_heap_d,
+            run_filter,
+            Vec::new(),
+        )
+    }
+
+    pub fn new_with_params(
+        graph: &'a dyn HSGraph<Node = N, Edge = E>,
+        paths: Vec<CHEdgeList>,
+        adaptive_threshold: usize,
+        explore_heap_d: usize,
+    ) -> Self {
+        Self::new_with_run_filter(graph, paths, adaptive_threshold, explore_heap_d, &|_| true)
+    }
+
+    pub fn new_with_threshold(
+        graph: &'a dyn HSGraph<Node = N, Edge = E>,
+        paths: Vec<CHEdgeList>,
+        adaptive_threshold: usize,
+    ) -> Self {
+        Self::new_with_params(graph, paths, adaptive_threshold, DEFAULT_EXPLORE_HEAP_D)
     }
 
     pub fn new(graph: &'a dyn HSGraph<Node = N, Edge = E>, paths: Vec<CHEdgeList>) -> Self {
         Self::new_with_threshold(graph, paths, 400000)
     }
 
+    /// the maximal runs of always-co-occurring base edges discovered at construction, for
+    /// inspection (e.g. benchmarking how much a given path set compresses).
+    pub fn runs(&self) -> &[EdgeRun] {
+        &self.runs
+    }
+
+    /// all path ids that have `edge` (at any run-compression level) on them.
+    fn paths_of(&self, edge: EdgeId) -> &[usize] {
+        &self.run_path_map[self.edge_to_run[edge]]
+    }
+
     /// finds all paths that intersect path_id. will ignore paths that are tagged as false in the paths_todo list.
     fn intersecting_paths(&self, path_id: usize, paths_todo: &Vec<bool>) -> FxHashSet<usize> {
         let mut res = FxHashSet::default();
@@ -66,7 +236,7 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
         // first, handle the down queue
         while let Some(edge) = down_queue.pop() {
             // any paths in the edge path map for this edge intersect the input path.
-            for &path in &self.edge_path_map[edge] {
+            for &path in self.paths_of(edge) {
                 if paths_todo[path] {
                     res.insert(path);
                 }
@@ -97,7 +267,7 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
                 // edge is base edge
                 let source = self.graph.edge(edge).source();
                 let target = self.graph.edge(edge).target();
-                for &parent in self.graph.node(source).parents() {
+                for &parent in self.graph.node_parents(source) {
                     if parent != edge {
                         if !up_visited[parent] {
                             up_queue.push(parent);
@@ -105,7 +275,7 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
                         }
                     }
                 }
-                for &parent in self.graph.node(target).parents() {
+                for &parent in self.graph.node_parents(target) {
                     if parent != edge {
                         if !up_visited[parent] {
                             up_queue.push(parent);
@@ -119,13 +289,13 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
         // handle up queue
         while let Some(edge) = up_queue.pop() {
             // any paths in the edge path map for this edge intersect the input path.
-            for &path in &self.edge_path_map[edge] {
+            for &path in self.paths_of(edge) {
                 if paths_todo[path] {
                     res.insert(path);
                 }
             }
             // add parents to the up queue.
-            for &parent in self.graph.edge(edge).parents() {
+            for &parent in self.graph.edge_parents(edge) {
                 if !up_visited[parent] {
                     up_queue.push(parent);
                     up_visited[parent] = true;
@@ -136,6 +306,7 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
     }
 
     /// finds a lower bound for the hitting set size
+    #[cfg(not(feature = "parallel-hittingset"))]
     pub fn lower_bound(&self) -> usize {
         let mut lower = 0;
 
@@ -156,6 +327,44 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
         lower
     }
 
+    /// finds a lower bound for the hitting set size.
+    /// each path's `intersecting_paths` DAG walk only depends on the path itself and the
+    /// (immutable) run_path_map - not on the other paths' todo state, which is just used to
+    /// keep the returned set small - so all of them can be computed against an "everything is
+    /// still todo" snapshot in parallel, up front. the greedy todo tagging and counting then
+    /// happens sequentially afterward, exactly as the non-parallel version would: marking an
+    /// already-untagged path false again is a no-op, so reusing the precomputed, unfiltered
+    /// intersections is still correct.
+    #[cfg(feature = "parallel-hittingset")]
+    pub fn lower_bound(&self) -> usize {
+        use rayon::prelude::*;
+
+        let all_todo: Vec<bool> = vec![true; self.paths.len()];
+        let intersections: Vec<FxHashSet<usize>> = (0..self.paths.len())
+            .into_par_iter()
+            .map(|id| {
+                if self.paths[id].len() > 0 {
+                    self.intersecting_paths(id, &all_todo)
+                } else {
+                    FxHashSet::default()
+                }
+            })
+            .collect();
+
+        let mut lower = 0;
+        let mut paths_todo: Vec<bool> = self.paths.iter().map(|p| p.len() > 0).collect();
+
+        for id in 0..self.paths.len() {
+            if paths_todo[id] {
+                for &entry in &intersections[id] {
+                    paths_todo[entry] = false;
+                }
+                lower += 1;
+            }
+        }
+        lower
+    }
+
     /// calculates the hitting set.
     pub fn run(self) -> Vec<(NodeId, u64)> {
         self.run_with_stats_maxiter(false, None)
@@ -165,45 +374,83 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
     /// print_stats: outputs information on each iteration
     /// maxiter: stop after reaching given number of iterations
     pub fn run_with_stats_maxiter(
-        mut self,
+        self,
         print_stats: bool,
         maxiter: Option<usize>,
+    ) -> Vec<(NodeId, u64)> {
+        if print_stats {
+            println!("iteration, iteration time, #hit paths, #paths left, weighted #hit paths");
+        }
+        self.run_with_callback(|stats| {
+            if print_stats {
+                println!(
+                    "{}, {:?}, {}, {}, {}",
+                    stats.iteration,      // iteration
+                    stats.elapsed,        // iteration time
+                    stats.paths_hit,      // #hit paths
+                    stats.paths_remaining, // #paths left
+                    stats.weight,         // weighted #hit paths
+                );
+            }
+            if maxiter.map_or(false, |mi| stats.iteration >= mi) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+    }
+
+    /// calculates the hitting set, invoking `cb` after every iteration with a snapshot of its
+    /// progress. `cb` returning `ControlFlow::Break` stops the run early, subsuming `maxiter` as a
+    /// special case (`run_with_stats_maxiter` is implemented on top of this). lets an embedder
+    /// drive a progress bar, a live plot, or an adaptive stopping rule instead of scraping stdout.
+    pub fn run_with_callback(
+        mut self,
+        mut cb: impl FnMut(&IterationStats) -> ControlFlow<()>,
     ) -> Vec<(NodeId, u64)> {
         // preparation: do one full scan
         self.scan_edges_full(false);
 
-        let mut hittingset = Vec::new();
-        let mut num_paths = self.paths.len();
+        // max-priority-queue over node occurence counts, keyed by self.hist. kept in sync lazily:
+        // after each scan only the touched nodes get a fresh entry pushed, so a popped entry whose
+        // stored count no longer matches self.hist[node] is stale and simply discarded.
+        let mut pq: BinaryHeap<NodeHeapElement> = (0..self.hist.len())
+            .map(|n| NodeHeapElement {
+                count: self.hist[n],
+                node: n.into(),
+            })
+            .collect();
+
+        let mut hittingset = std::mem::take(&mut self.resume);
+        let mut num_paths = self.paths.iter().filter(|p| p.len() > 0).count();
+        let mut weight_remaining: u64 = self
+            .paths
+            .iter()
+            .filter(|p| p.len() > 0)
+            .map(|p| p.weight)
+            .sum();
         let mut iteration = 0;
 
-        if print_stats {
-            println!("iteration, iteration time, #hit paths, #paths left, weighted #hit paths");
-        }
         loop {
             iteration += 1;
 
-            // early stopping check
-            if let Some(mi) = maxiter {
-                if iteration > mi {
-                    return hittingset;
-                }
-            }
-
             let now = Instant::now();
-            // find hitter
-            let (max_id, &max_occ) = self
-                .hist
-                .iter()
-                .enumerate()
-                .max_by(|(_, v), (_, w)| v.cmp(w))
-                .unwrap();
-            let hitter = max_id.into();
+            // find hitter: pop stale entries (count no longer matches the current hist) until we
+            // find one that is still current - that is the true max.
+            let (hitter, max_occ) = loop {
+                let NodeHeapElement { count, node } = pq
+                    .pop()
+                    .expect("node heap should not be empty while the graph has nodes");
+                if count == self.hist[node] {
+                    break (node, count);
+                }
+                // stale entry - this node's count has since changed, discard and keep looking
+            };
             if max_occ == 0 {
                 // no paths left - stop
                 break;
             }
 
-
             let mut removed = Vec::new();
 
             // find & remove paths that were hit (dont delete entries from the list to keep indices stable)
@@ -216,32 +463,44 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
                 p.clear();
             }
             num_paths -= removed.len();
+            let hit_weight: u64 = removed.iter().map(|r| r.weight).sum();
+            weight_remaining -= hit_weight;
 
             // add to set
-            hittingset.push((hitter, removed.iter().map(|r| r.weight).sum::<u64>()));
+            hittingset.push((hitter, hit_weight));
 
             // if there is an input of size < adaptive_threshold, run an explorative scan. otherwise full scan.
-            if removed.len() < self.adaptive_threshold || num_paths < self.adaptive_threshold {
+            let touched = if removed.len() < self.adaptive_threshold || num_paths < self.adaptive_threshold
+            {
                 if removed.len() < num_paths {
-                    self.scan_edges_explore(Some(&removed), true);
+                    self.scan_edges_explore(Some(&removed), true)
                 } else {
-                    self.scan_edges_explore(None, false);
+                    self.scan_edges_explore(None, false)
                 }
             } else {
-                self.scan_edges_full(false);
+                self.scan_edges_full(false)
+            };
+
+            // only the touched nodes' counts changed - push their new values. entries for
+            // untouched nodes are still current and stay where they are in the heap.
+            for node in touched {
+                pq.push(NodeHeapElement {
+                    count: self.hist[node],
+                    node,
+                });
             }
 
-            // print stats
-            if print_stats {
-                // println!("iteration, iteration time, #hit paths, #paths left, weighted #hit paths");
-                println!(
-                    "{}, {:?}, {}, {}, {}",
-                    iteration,                                     // iteration
-                    now.elapsed(),                                 // iteration time
-                    removed.len(),                                 // #hit paths
-                    num_paths,                                     // #paths left
-                    removed.iter().map(|r| r.weight).sum::<u64>(), // weighted #hit paths
-                );
+            let stats = IterationStats {
+                iteration,
+                node: hitter,
+                weight: hit_weight,
+                paths_hit: removed.len(),
+                paths_remaining: num_paths,
+                weight_remaining,
+                elapsed: now.elapsed(),
+            };
+            if cb(&stats).is_break() {
+                break;
             }
         }
 
@@ -253,13 +512,13 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
         // traverse the DAG and check every edge for path parents.
         let mut hit_paths = FxHashSet::default();
 
-        let mut queue: Vec<EdgeId> = self.graph.node(hitter).parents().iter().cloned().collect();
+        let mut queue: Vec<EdgeId> = self.graph.node_parents(hitter).iter().cloned().collect();
 
         while let Some(edge) = queue.pop() {
-            for &path in &self.edge_path_map[edge] {
+            for &path in self.paths_of(edge) {
                 hit_paths.insert(path);
             }
-            for &parent in self.graph.edge(edge).parents() {
+            for &parent in self.graph.edge_parents(edge) {
                 queue.push(parent);
             }
         }
@@ -267,30 +526,95 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
         hit_paths
     }
 
+    /// counts edge occurrences (weight-summed) over `paths`, returning `(edges_hist,
+    /// source_counts)` where `source_counts[node]` is the weight-summed count of paths starting
+    /// at `node`. sequential baseline - see `count_edges_parallel` for the rayon-backed variant.
+    #[cfg(not(feature = "parallel-hittingset"))]
+    fn count_edges(&self, paths: &[CHEdgeList]) -> (Vec<u64>, Vec<u64>) {
+        let mut edges_hist = vec![0u64; self.graph.num_edges()];
+        let mut source_counts = vec![0u64; self.hist.len()];
+        for path in paths {
+            if let Some(&first_edge) = path.first() {
+                let source = self.graph.edge(first_edge).source();
+                source_counts[source] += path.weight;
+            }
+            for &edge in path {
+                edges_hist[edge] += path.weight;
+            }
+        }
+        (edges_hist, source_counts)
+    }
+
+    /// same as `count_edges`, but maps-and-reduces per-thread `(edges_hist, source_counts)`
+    /// vectors over `paths` with rayon instead of folding sequentially.
+    #[cfg(feature = "parallel-hittingset")]
+    fn count_edges_parallel(&self, paths: &[CHEdgeList]) -> (Vec<u64>, Vec<u64>) {
+        use rayon::prelude::*;
+
+        let num_edges = self.graph.num_edges();
+        let num_nodes = self.hist.len();
+
+        paths
+            .par_iter()
+            .fold(
+                || (vec![0u64; num_edges], vec![0u64; num_nodes]),
+                |(mut edges_hist, mut source_counts), path| {
+                    if let Some(&first_edge) = path.first() {
+                        let source = self.graph.edge(first_edge).source();
+                        source_counts[source] += path.weight;
+                    }
+                    for &edge in path {
+                        edges_hist[edge] += path.weight;
+                    }
+                    (edges_hist, source_counts)
+                },
+            )
+            .reduce(
+                || (vec![0u64; num_edges], vec![0u64; num_nodes]),
+                |(mut eh1, mut sc1), (eh2, sc2)| {
+                    for (a, b) in eh1.iter_mut().zip(eh2) {
+                        *a += b;
+                    }
+                    for (a, b) in sc1.iter_mut().zip(sc2) {
+                        *a += b;
+                    }
+                    (eh1, sc1)
+                },
+            )
+    }
+
     /// runs a full scan of all paths, updating the histogram.
     /// update == true => update old hist (new = old - this)
     /// update == false => create new hist (new = this)
-    fn scan_edges_full(&mut self, update: bool) {
+    /// returns the set of nodes whose hist entry was touched, so callers can resync their
+    /// node-priority-queue without re-pushing every node.
+    fn scan_edges_full(&mut self, update: bool) -> Vec<NodeId> {
         if !update {
             // reset hist
             self.hist = vec![0; self.hist.len()];
         }
-        let mut edges_hist: Vec<u64> = vec![0; self.graph.num_edges()];
-        // 1. count edges
-        for path in &self.paths {
-            // we will count the target node of each edge. this will skip the source node of each path -> add them here
-            if let Some(&first_edge) = path.first() {
-                let source = self.graph.edge(first_edge).source();
 
-                if update {
-                    self.hist[source] -= path.weight; // sub because we want to update old data
-                } else {
-                    self.hist[source] += path.weight;
-                }
+        // 1. count edges. feature-gated: the per-path counting is embarrassingly parallel (every
+        // path only ever adds to edges_hist/source_counts), so the rayon-backed variant folds
+        // per-thread vectors and sums them; the source-node hist update itself stays here,
+        // sequential, either way.
+        #[cfg(feature = "parallel-hittingset")]
+        let (mut edges_hist, source_counts) = self.count_edges_parallel(&self.paths);
+        #[cfg(not(feature = "parallel-hittingset"))]
+        let (mut edges_hist, source_counts) = self.count_edges(&self.paths);
+
+        let mut touched = FxHashSet::default();
+        for (node, &count) in source_counts.iter().enumerate() {
+            if count == 0 {
+                continue;
             }
-            for &edge in path {
-                edges_hist[edge] += path.weight;
+            let node: NodeId = node.into();
+            if update {
+                self.hist[node] -= count; // sub because we want to update old data
+            } else {
+                self.hist[node] += count;
             }
+            touched.insert(node);
         }
 
         // 2. replace ch-edges with children, use topological order
@@ -307,15 +631,23 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
                 } else {
                     self.hist[target] += edges_hist[edge.id()];
                 }
+                touched.insert(target);
             }
         }
+        touched.into_iter().collect()
     }
 
     /// runs an explorative scan, updating the histogram.
     /// update == true => update old hist
     /// update == false => create new hist
     /// removed_paths: if Some, scan these paths. if None, scan self.paths
-    fn scan_edges_explore(&mut self, removed_paths: Option<&Vec<CHEdgeList>>, update: bool) {
+    /// returns the set of nodes whose hist entry was touched, so callers can resync their
+    /// node-priority-queue without re-pushing every node.
+    fn scan_edges_explore(
+        &mut self,
+        removed_paths: Option<&Vec<CHEdgeList>>,
+        update: bool,
+    ) -> Vec<NodeId> {
         let paths = match removed_paths {
             Some(p) => p,
             None => &self.paths,
@@ -326,6 +658,7 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
             self.hist = vec![0; self.hist.len()];
         }
         let mut edges_hist: Vec<u64> = vec![0; self.graph.num_edges()];
+        let mut touched = FxHashSet::default();
 
         // 1. count edges
         for path in paths {
@@ -338,13 +671,15 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
                 } else {
                     self.hist[source] += path.weight;
                 }
+                touched.insert(source);
             }
             for &edge in path {
                 edges_hist[edge] += path.weight;
             }
         }
 
-        // 2. replace ch-edges with children, sorted by node level. (uses a binary heap for sorting)
+        // 2. replace ch-edges with children, sorted by node level. (uses a d-ary heap for sorting -
+        // this phase is push-heavy, up to two pushes per pop, so the shallower tree pays off)
 
         // collect set of all edges in given paths
         let mut unique_edges: Vec<EdgeId> = paths.iter().flatten().cloned().collect();
@@ -352,13 +687,16 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
         unique_edges.dedup();
 
         // create initial heap, use unique edge set to prevent duplicate entries
-        let mut pq: BinaryHeap<CHEdgeHeapElement> = unique_edges
-            .into_iter()
-            .map(|e| CHEdgeHeapElement {
-                edge: e,
-                prio: self.graph.toporder(e),
-            })
-            .collect();
+        let mut pq: DaryHeap<CHEdgeHeapElement> = DaryHeap::from_vec(
+            self.explore_heap_d,
+            unique_edges
+                .into_iter()
+                .map(|e| CHEdgeHeapElement {
+                    edge: e,
+                    prio: self.graph.toporder(e),
+                })
+                .collect(),
+        );
 
         // iterate heap and update histogram, fill node histogram
         while let Some(CHEdgeHeapElement { edge, .. }) = pq.pop() {
@@ -398,8 +736,30 @@ impl<'a, N: HSNode + BaseNode, E: HSEdge + BaseEdge + ChildEdge> HittingSet<'a,
                 } else {
                     self.hist[target] += edges_hist[edge];
                 }
+                touched.insert(target);
             }
         }
+        touched.into_iter().collect()
+    }
+}
+
+/// lazy-deletion max-heap entry used by `run_with_callback` to find the next hitter without
+/// rescanning `self.hist` every iteration. orders by occurence count first, then by `NodeId`,
+/// mirroring `CHEdgeHeapElement`'s value-then-id ordering pattern.
+#[derive(Eq, PartialEq)]
+struct NodeHeapElement {
+    count: u64,
+    node: NodeId,
+}
+
+impl Ord for NodeHeapElement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.count.cmp(&other.count).then(self.node.cmp(&other.node))
+    }
+}
+impl PartialOrd for NodeHeapElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
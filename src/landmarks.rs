@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{BaseGraph, BaseNode, CHDirection, CHEdge, CHGraph, GeoNode, NodeId, StoreableGraph};
+
+/// precomputed landmark distance tables for the ALT (A*, Landmarks, Triangle inequality) heuristic.
+///
+/// `dist_from[l][v]` is the shortest distance landmark `l` -> node `v`.
+/// `dist_to[l][v]` is the shortest distance node `v` -> landmark `l`.
+#[derive(Serialize, Deserialize)]
+pub struct Landmarks {
+    landmarks: Vec<NodeId>,
+    dist_from: Vec<Vec<u32>>,
+    dist_to: Vec<Vec<u32>>,
+}
+
+impl StoreableGraph for Landmarks {
+    fn from_file_binary(filename: &str) -> Result<Self, bincode::Error> {
+        let file = std::fs::File::open(filename)?;
+        let reader = std::io::BufReader::new(file);
+        bincode::deserialize_from(reader)
+    }
+}
+
+impl Landmarks {
+    /// selects `num_landmarks` well-spread landmarks via farthest-point sampling over lat/lon
+    /// and precomputes forward/backward distances from every landmark to every node.
+    pub fn new<N, E>(graph: &dyn CHGraph<Node = N, Edge = E>, num_landmarks: usize) -> Self
+    where
+        N: BaseNode + GeoNode,
+        E: CHEdge,
+    {
+        let landmarks = select_landmarks(graph, num_landmarks.min(graph.num_nodes()));
+
+        let mut dist_from = Vec::with_capacity(landmarks.len());
+        let mut dist_to = Vec::with_capacity(landmarks.len());
+
+        for &l in &landmarks {
+            dist_from.push(single_source(graph, l, CHDirection::Both));
+            dist_to.push(single_source_reverse(graph, l));
+        }
+
+        Self {
+            landmarks,
+            dist_from,
+            dist_to,
+        }
+    }
+
+    pub fn num_landmarks(&self) -> usize {
+        self.landmarks.len()
+    }
+
+    /// admissible, consistent A* heuristic: h(v) = max over landmarks of
+    /// max(d(v,L) - d(t,L), d(L,t) - d(L,v)), clamped at 0
+    pub fn heuristic(&self, v: NodeId, t: NodeId) -> u32 {
+        let mut best = 0u32;
+        for i in 0..self.landmarks.len() {
+            let d_v_l = self.dist_to[i][v];
+            let d_t_l = self.dist_to[i][t];
+            let d_l_v = self.dist_from[i][v];
+            let d_l_t = self.dist_from[i][t];
+
+            if d_v_l != u32::MAX && d_t_l != u32::MAX && d_v_l >= d_t_l {
+                best = best.max(d_v_l - d_t_l);
+            }
+            if d_l_t != u32::MAX && d_l_v != u32::MAX && d_l_t >= d_l_v {
+                best = best.max(d_l_t - d_l_v);
+            }
+        }
+        best
+    }
+}
+
+/// picks `k` nodes spread out over the graph via farthest-point sampling on lat/lon.
+/// distances are tracked in meters (rounded) so they can be indexed by `NodeId` like the rest of the crate.
+fn select_landmarks<N, E>(graph: &dyn CHGraph<Node = N, Edge = E>, k: usize) -> Vec<NodeId>
+where
+    N: BaseNode + GeoNode,
+    E: CHEdge,
+{
+    if k == 0 || graph.num_nodes() == 0 {
+        return Vec::new();
+    }
+
+    let mut chosen = vec![graph.node(0.into()).id()];
+    let mut best_dist: Vec<u32> = vec![u32::MAX; graph.num_nodes()];
+
+    while chosen.len() < k {
+        let last = graph.node(*chosen.last().unwrap());
+        for node in graph.iter_nodes() {
+            let d = haversine_meters(last.lat(), last.lon(), node.lat(), node.lon());
+            if d < best_dist[node.id()] {
+                best_dist[node.id()] = d;
+            }
+        }
+
+        let next = graph
+            .iter_nodes()
+            .map(|n| n.id())
+            .max_by_key(|&id| best_dist[id])
+            .unwrap();
+        chosen.push(next);
+    }
+
+    chosen
+}
+
+/// the minimum, over every edge, of cost-per-meter (edge cost divided by its endpoints'
+/// great-circle distance). scaling a straight-line distance to `dest` by this factor keeps
+/// `Dijkstra::geo_search`'s heuristic admissible, since no edge in the graph is ever cheaper per
+/// meter than this - so the heuristic never overestimates the true remaining cost.
+pub fn min_cost_per_meter<N, E>(graph: &dyn CHGraph<Node = N, Edge = E>) -> f64
+where
+    N: BaseNode + GeoNode,
+    E: CHEdge,
+{
+    graph
+        .iter_edges()
+        .filter_map(|e| {
+            let source = graph.node(e.source());
+            let target = graph.node(e.target());
+            let meters = haversine_meters(source.lat(), source.lon(), target.lat(), target.lon());
+            (meters > 0).then(|| e.cost() as f64 / meters as f64)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// great-circle distance in meters, rounded to the nearest integer
+pub(crate) fn haversine_meters(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> u32 {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let (lat1, lon1, lat2, lon2) = (
+        lat1.to_radians(),
+        lon1.to_radians(),
+        lat2.to_radians(),
+        lon2.to_radians(),
+    );
+    let dlat = lat2 - lat1;
+    let dlon = lon2 - lon1;
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    (2.0 * EARTH_RADIUS_M * a.sqrt().asin()).round() as u32
+}
+
+#[derive(Eq, PartialEq)]
+struct HeapEntry {
+    cost: u32,
+    id: NodeId,
+}
+
+// inverse ordering to create a min heap (BinaryHeap is a maxheap)
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost).then(other.id.cmp(&self.id))
+    }
+}
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// plain single-source dijkstra over the requested CHDirection, returning distances to every node
+fn single_source<N, E>(
+    graph: &dyn CHGraph<Node = N, Edge = E>,
+    source: NodeId,
+    direction: CHDirection,
+) -> Vec<u32>
+where
+    N: BaseNode,
+    E: CHEdge,
+{
+    let mut dist: Vec<u32> = vec![u32::MAX; graph.num_nodes()];
+    let mut heap = BinaryHeap::new();
+    dist[source] = 0;
+    heap.push(HeapEntry { cost: 0, id: source });
+
+    while let Some(HeapEntry { cost, id }) = heap.pop() {
+        if cost > dist[id] {
+            continue;
+        }
+        for &edge in graph.out_edges(id, direction) {
+            let e = graph.edge(edge);
+            let next = cost + e.cost();
+            if next < dist[e.target()] {
+                dist[e.target()] = next;
+                heap.push(HeapEntry {
+                    cost: next,
+                    id: e.target(),
+                });
+            }
+        }
+    }
+    dist
+}
+
+/// single-source dijkstra on the reverse graph (using in-edges), returning dist(v, source) for every v
+fn single_source_reverse<N, E>(graph: &dyn CHGraph<Node = N, Edge = E>, source: NodeId) -> Vec<u32>
+where
+    N: BaseNode,
+    E: CHEdge,
+{
+    let mut dist: Vec<u32> = vec![u32::MAX; graph.num_nodes()];
+    let mut heap = BinaryHeap::new();
+    dist[source] = 0;
+    heap.push(HeapEntry { cost: 0, id: source });
+
+    while let Some(HeapEntry { cost, id }) = heap.pop() {
+        if cost > dist[id] {
+            continue;
+        }
+        for &edge in graph.in_edges(id, CHDirection::Both) {
+            let e = graph.edge(edge);
+            let next = cost + e.cost();
+            if next < dist[e.source()] {
+                dist[e.source()] = next;
+                heap.push(HeapEntry {
+                    cost: next,
+                    id: e.source(),
+                });
+            }
+        }
+    }
+    dist
+}
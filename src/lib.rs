@@ -1,85 +1,223 @@
+pub mod cache;
+pub mod dary_heap;
 pub mod dijkstra;
+pub mod distance_oracle;
 pub mod file_handling;
 pub mod graph;
 pub mod hittingset;
+pub mod landmarks;
 pub mod paths;
 pub mod quadtree;
 pub mod random_pairs;
+pub mod rstartree;
+pub mod rtree;
 pub mod vis;
 pub mod wspd;
 
 use std::{
     fs::{File, OpenOptions},
-    io::{BufRead, BufReader, BufWriter},
+    io::{BufRead, BufReader, BufWriter, Read, Write},
 };
 
 use csv::{ReaderBuilder, Writer, WriterBuilder};
-use graph::{AdjArrayGraph, FMIGraph, NodeId, StoreableGraph, ToporderedGraph};
+use graph::{AdjArrayGraph, FMIGraph, MmapFMIGraph, NodeId, StoreableGraph, ToporderedGraph};
 use paths::CHEdgeList;
 
 
 // helper functions for loading and caching data
 
-/// load a FMIGraph from a .fmi text file or a cache file if available
+/// load a FMIGraph from a .fmi text file or a cache file if available. the cache is keyed to a
+/// fingerprint of `filename`'s length and mtime, so editing or replacing the source file after the first
+/// load is picked up instead of silently handing back a stale cached graph.
 pub fn load_fmi_graph(filename: &str) -> Result<FMIGraph, Box<dyn std::error::Error>> {
-    match FMIGraph::from_file_binary(&[filename, ".fmigraph"].concat()) {
+    match FMIGraph::from_file_binary_checked(&[filename, ".fmigraph"].concat(), filename) {
         Ok(g) => Ok(g),
         Err(_) => {
             let g = FMIGraph::from_fmi_maxspeed_ch_txt(&filename)?;
-            g.to_file_binary(&[filename, ".fmigraph"].concat())?;
+            g.to_file_binary_checked(&[filename, ".fmigraph"].concat(), filename)?;
             Ok(g)
         }
     }
 }
-/// load a CHGraph from a .fmi text file or a cache file if available
+
+/// same as `load_fmi_graph`, but parses the .fmi text file with `from_fmi_maxspeed_ch_txt_parallel`
+/// instead of the sequential reader. caches to the same `.fmigraph` file, so whichever of the two
+/// loaders runs first decides what later runs (parallel or not) load from.
+pub fn load_fmi_graph_parallel(filename: &str) -> Result<FMIGraph, Box<dyn std::error::Error>> {
+    match FMIGraph::from_file_binary_checked(&[filename, ".fmigraph"].concat(), filename) {
+        Ok(g) => Ok(g),
+        Err(_) => {
+            let g = FMIGraph::from_fmi_maxspeed_ch_txt_parallel(&filename)?;
+            g.to_file_binary_checked(&[filename, ".fmigraph"].concat(), filename)?;
+            Ok(g)
+        }
+    }
+}
+
+/// load a FMIGraph from a .fmi text file or cache, same as `load_fmi_graph`, but hand back a
+/// zero-copy `MmapFMIGraph` view instead of eagerly deserializing everything into memory - useful
+/// for graphs too large to comfortably fit in RAM. an existing `.mmapgraph` file is reused as is;
+/// unlike the `.fmigraph` cache it is not checked against `filename`'s fingerprint.
+pub fn load_fmi_graph_mmap(filename: &str) -> Result<MmapFMIGraph, Box<dyn std::error::Error>> {
+    let mmap_file = [filename, ".mmapgraph"].concat();
+    if !std::path::Path::new(&mmap_file).exists() {
+        let g = load_fmi_graph(filename)?;
+        g.to_file_mmap(&mmap_file)?;
+    }
+    Ok(MmapFMIGraph::open(&mmap_file)?)
+}
+/// load a CHGraph from a .fmi text file or a cache file if available. validated against
+/// `filename`'s fingerprint the same way as `load_fmi_graph`.
 pub fn load_ch_graph(filename: &str) -> Result<AdjArrayGraph, Box<dyn std::error::Error>> {
-    match AdjArrayGraph::from_file_binary(&[filename, ".chgraph"].concat()) {
+    match AdjArrayGraph::from_file_binary_checked(&[filename, ".chgraph"].concat(), filename) {
         Ok(g) => Ok(g),
         Err(_) => {
             let g = load_fmi_graph(filename)?;
             let g: AdjArrayGraph = g.into();
-            g.to_file_binary(&[filename, ".chgraph"].concat())?;
+            g.to_file_binary_checked(&[filename, ".chgraph"].concat(), filename)?;
             Ok(g)
         }
     }
 }
 
-/// load a HSGraph from a .fmi text file or a cache file if available
+/// load a HSGraph from a .fmi text file or a cache file if available. validated against
+/// `filename`'s fingerprint the same way as `load_fmi_graph`.
 pub fn load_hs_graph(filename: &str) -> Result<ToporderedGraph, Box<dyn std::error::Error>> {
-    match ToporderedGraph::from_file_binary(&[filename, ".hsgraph"].concat()) {
+    match ToporderedGraph::from_file_binary_checked(&[filename, ".hsgraph"].concat(), filename) {
         Ok(g) => Ok(g),
         Err(_) => {
             let g = load_fmi_graph(filename)?;
             let g: ToporderedGraph = g.into();
-            g.to_file_binary(&[filename, ".hsgraph"].concat())?;
+            g.to_file_binary_checked(&[filename, ".hsgraph"].concat(), filename)?;
             Ok(g)
         }
     }
 }
 
-/// load a paths file
+/// magic bytes identifying the binary paths format (see `PathFormat::Binary`); the text format
+/// has no such header, so a file not starting with this is assumed to be the old csv format
+const PATH_BINARY_MAGIC: &[u8; 8] = b"GARPPTH\0";
+/// bumped whenever the binary record layout changes, so a future reader can reject (rather than
+/// silently misparse) a file written by an incompatible version
+const PATH_BINARY_VERSION: u32 = 1;
+
+/// on-disk representation used by `PathWriter`/`load_paths`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PathFormat {
+    /// the original line-oriented csv format, one path per line
+    Text,
+    /// length-prefixed bincode records behind a magic/version header, see `PATH_BINARY_MAGIC`
+    Binary,
+}
+
+/// load a paths file, auto-detecting text vs binary format from its header
 pub fn load_paths(filename: &str) -> Result<Vec<CHEdgeList>, Box<dyn std::error::Error>> {
-    let mut rdr = ReaderBuilder::new()
-        .flexible(true)
-        .has_headers(false)
-        .from_path(filename)
-        .unwrap();
+    let mut file = File::open(filename)?;
+    let mut magic = [0u8; 8];
+    let is_binary = file.read_exact(&mut magic).is_ok() && &magic == PATH_BINARY_MAGIC;
 
-    let mut paths = Vec::new();
+    if !is_binary {
+        let mut rdr = ReaderBuilder::new()
+            .flexible(true)
+            .has_headers(false)
+            .from_path(filename)
+            .unwrap();
 
-    for record in rdr.deserialize() {
-        let path: CHEdgeList = record?;
-        paths.push(path);
+        let mut paths = Vec::new();
+        for record in rdr.deserialize() {
+            let path: CHEdgeList = record?;
+            paths.push(path);
+        }
+        return Ok(paths);
+    }
+
+    let mut version = [0u8; 4];
+    file.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != PATH_BINARY_VERSION {
+        return Err(format!("unsupported binary paths file version {}", version).into());
+    }
+
+    let mut reader = BufReader::new(file);
+    let mut paths = Vec::new();
+    let mut len_buf = [0u8; 8];
+    loop {
+        match reader.read_exact(&mut len_buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let len = u64::from_le_bytes(len_buf) as usize;
+        let mut record = vec![0u8; len];
+        reader.read_exact(&mut record)?;
+        paths.push(bincode::deserialize(&record)?);
     }
     Ok(paths)
 }
 
+enum PathWriterInner {
+    Text(Writer<BufWriter<File>>),
+    Binary(BufWriter<File>),
+}
+
 pub struct PathWriter {
-    wtr: Writer<BufWriter<File>>,
+    inner: PathWriterInner,
 }
 
 impl PathWriter {
-    pub fn new(filename: &str, append: bool) -> Self {
+    pub fn new(filename: &str, append: bool, format: PathFormat) -> Self {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(filename)
+            .expect(&format!("creating out file {} failed", filename));
+
+        let inner = match format {
+            PathFormat::Text => {
+                let buf = BufWriter::new(file);
+                let wtr = WriterBuilder::new()
+                    .flexible(true)
+                    .has_headers(false)
+                    .from_writer(buf);
+                PathWriterInner::Text(wtr)
+            }
+            PathFormat::Binary => {
+                let mut buf = BufWriter::new(file);
+                if !append {
+                    buf.write_all(PATH_BINARY_MAGIC).unwrap();
+                    buf.write_all(&PATH_BINARY_VERSION.to_le_bytes()).unwrap();
+                }
+                PathWriterInner::Binary(buf)
+            }
+        };
+
+        Self { inner }
+    }
+
+    /// store a paths file
+    pub fn save_path(&mut self, path: CHEdgeList) {
+        match &mut self.inner {
+            PathWriterInner::Text(wtr) => wtr.serialize(path).unwrap(),
+            PathWriterInner::Binary(buf) => {
+                let record = bincode::serialize(&path).unwrap();
+                buf.write_all(&(record.len() as u64).to_le_bytes()).unwrap();
+                buf.write_all(&record).unwrap();
+            }
+        }
+    }
+}
+
+/// writes paths to a file in batches, to cut channel/serialization overhead when many
+/// producers (e.g. rayon workers) each emit a large number of short-lived paths
+pub struct BatchedPathWriter {
+    wtr: Writer<BufWriter<File>>,
+    batch_size: usize,
+}
+
+impl BatchedPathWriter {
+    pub fn new(filename: &str, append: bool, batch_size: usize) -> Self {
         let file = OpenOptions::new()
             .write(true)
             .create(true)
@@ -94,12 +232,19 @@ impl PathWriter {
             .has_headers(false)
             .from_writer(buf);
 
-        Self { wtr }
+        Self { wtr, batch_size }
     }
 
-    /// store a paths file
-    pub fn save_path(&mut self, path: CHEdgeList) {
-        self.wtr.serialize(path).unwrap();
+    /// the writer's preferred batch granularity, so senders can size their buffers to match
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// store a batch of paths in one serialization call
+    pub fn save_batch(&mut self, batch: &[CHEdgeList]) {
+        for path in batch {
+            self.wtr.serialize(path).unwrap();
+        }
     }
 }
 
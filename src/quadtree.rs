@@ -1,9 +1,14 @@
 use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
     fmt::Debug,
     hash::{Hash, Hasher},
     ops::Deref,
 };
 
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+
 use crate::{
     graph::{BaseNode, CHNode, GeoNode},
     wspd::{Distance, Tree},
@@ -12,6 +17,27 @@ use crate::{
 pub trait TreeNode: GeoNode + CHNode + BaseNode + Sync {}
 impl<N> TreeNode for N where N: GeoNode + CHNode + BaseNode + Sync {}
 
+/// minimum combined point count across all four quadrants of a `new_cell` split before its
+/// subtrees are built concurrently via `rayon::join` instead of sequentially; keeps task-spawn
+/// overhead from dominating near the leaves, where splits are small and numerous
+const PARALLEL_BUILD_THRESHOLD: usize = 4096;
+
+/// gives callers that are generic over the `wspd::Tree` implementor (e.g. `QuadTree` vs an
+/// alternative spatial index) a common way to recover the points stored under a pair's subtree
+pub trait PointContainer<'a> {
+    fn points(&'a self) -> Box<dyn Iterator<Item = &&'a dyn TreeNode> + 'a>;
+    fn size(&self) -> usize;
+}
+
+impl<'a> PointContainer<'a> for QuadTree<'a> {
+    fn points(&'a self) -> Box<dyn Iterator<Item = &&'a dyn TreeNode> + 'a> {
+        QuadTree::points(self)
+    }
+    fn size(&self) -> usize {
+        QuadTree::size(self)
+    }
+}
+
 struct Entry<'a> {
     point: &'a dyn TreeNode,
     x: f64,
@@ -103,10 +129,118 @@ pub struct QuadTree<'a> {
     pub ymax: f64,
     data: Vec<&'a dyn TreeNode>,
     scaler: MinMaxScaler,
-    pub id: String, // define:  a b
+    pub id: CellId, // define:  a b
                     //          c d
-                    // and self: ""
-                    // ordered from bottom to top: cba => topleft then topright then bottomleft
+                    // and self: root / CellId::ROOT
+    /// `id` rendered to the old `abcd` string, cached once at construction so `Tree::id` can
+    /// keep returning `&str` without re-rendering on every call
+    id_str: String,
+    /// node ids of every point in this cell's subtree, cached once at construction. backs
+    /// `node_bitmap` so WSPD covering/intersection checks get O(1) cardinality and cheap set
+    /// arithmetic instead of recursively walking `data` through every descendant cell.
+    bitmap: RoaringBitmap,
+}
+
+/// a quadtree cell path packed into two integers instead of a heap-allocated `a`/`b`/`c`/`d`
+/// string: each level contributes a 2-bit quadrant (a=00 top-left, b=01 top-right, c=10
+/// bottom-left, d=11 bottom-right) shifted into `code` from the root downward, and `depth`
+/// records how many levels are valid (up to 32, far beyond any realistic `maxdepth`). deriving a
+/// child's id is then a single shift-and-or with no allocation, and equality/hashing compare two
+/// integers instead of walking a string. [`Display`]/[`FromStr`] still render/parse the original
+/// `abcd` string so existing path output and any serialized ids round-trip.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CellId {
+    code: u64,
+    depth: u8,
+}
+
+impl CellId {
+    pub const ROOT: CellId = CellId { code: 0, depth: 0 };
+
+    /// derives the child key for `quadrant` (0=a, 1=b, 2=c, 3=d) in O(1), no allocation
+    fn child(&self, quadrant: u64) -> CellId {
+        CellId {
+            code: self.code | (quadrant << (2 * self.depth as u32)),
+            depth: self.depth + 1,
+        }
+    }
+
+    /// the path to this cell's parent, dropping the last 2-bit quadrant; the root is its own
+    /// parent, so callers walking an ancestor chain should stop once `is_empty()` is true
+    pub fn parent(&self) -> CellId {
+        if self.depth == 0 {
+            return *self;
+        }
+        CellId {
+            code: self.code & ((1u64 << (2 * (self.depth as u32 - 1))) - 1),
+            depth: self.depth - 1,
+        }
+    }
+
+    /// number of levels below the root - equivalent to the old string id's length
+    pub fn len(&self) -> usize {
+        self.depth as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth == 0
+    }
+}
+
+/// error returned by [`CellId`]'s [`FromStr`] impl for malformed cell-id strings
+#[derive(Debug)]
+pub struct ParseCellIdError;
+
+impl std::fmt::Display for ParseCellIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid quadtree cell id: expected only 'a'/'b'/'c'/'d' characters, at most 32 of them"
+        )
+    }
+}
+impl std::error::Error for ParseCellIdError {}
+
+impl std::fmt::Display for CellId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for level in (0..self.depth).rev() {
+            let quadrant = (self.code >> (2 * level as u32)) & 0b11;
+            let ch = match quadrant {
+                0 => 'a',
+                1 => 'b',
+                2 => 'c',
+                3 => 'd',
+                _ => unreachable!(),
+            };
+            write!(f, "{}", ch)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for CellId {
+    type Err = ParseCellIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > 32 {
+            return Err(ParseCellIdError);
+        }
+        let mut code: u64 = 0;
+        for (level, ch) in s.chars().rev().enumerate() {
+            let quadrant: u64 = match ch {
+                'a' => 0,
+                'b' => 1,
+                'c' => 2,
+                'd' => 3,
+                _ => return Err(ParseCellIdError),
+            };
+            code |= quadrant << (2 * level as u32);
+        }
+        Ok(CellId {
+            code,
+            depth: s.len() as u8,
+        })
+    }
 }
 
 impl<'a> PartialEq for QuadTree<'a> {
@@ -186,42 +320,32 @@ impl<'a> QuadTree<'a> {
         ((a.0 - b.0) * (a.0 - b.0) + (a.1 - b.1) * (a.1 - b.1)).sqrt()
     }
 
-    /// returns (sub) tree by id
-    pub fn get_by_id(&self, mut id: String) -> &Self {
-        let next = id.pop();
-        match next {
-            None => &self,
-            Some('a') => {
-                if let Some(child) = &self.children.a {
-                    child.get_by_id(id)
-                } else {
-                    &self
-                }
-            }
-            Some('b') => {
-                if let Some(child) = &self.children.b {
-                    child.get_by_id(id)
-                } else {
-                    &self
-                }
-            }
-            Some('c') => {
-                if let Some(child) = &self.children.c {
-                    child.get_by_id(id)
-                } else {
-                    &self
-                }
-            }
-            Some('d') => {
-                if let Some(child) = &self.children.d {
-                    child.get_by_id(id)
-                } else {
-                    &self
-                }
-            }
-            Some(_) => {
-                unreachable!("id string contains char not in [abcd]");
-            }
+    /// returns (sub) tree by id. accepts the same `abcd` path strings `Display`/`FromStr` on
+    /// `CellId` round-trip, but descends by decoding 2 bits per level instead of popping chars.
+    pub fn get_by_id(&self, id: String) -> &Self {
+        let cell_id: CellId = id.parse().expect("id string contains char not in [abcd]");
+        self.get_by_cell_id(cell_id)
+    }
+
+    fn get_by_cell_id(&self, id: CellId) -> &Self {
+        if id.is_empty() {
+            return self;
+        }
+        let quadrant = id.code & 0b11;
+        let rest = CellId {
+            code: id.code >> 2,
+            depth: id.depth - 1,
+        };
+        let child = match quadrant {
+            0 => &self.children.a,
+            1 => &self.children.b,
+            2 => &self.children.c,
+            3 => &self.children.d,
+            _ => unreachable!(),
+        };
+        match child {
+            Some(child) => child.get_by_cell_id(rest),
+            None => self,
         }
     }
 
@@ -239,6 +363,128 @@ impl<'a> QuadTree<'a> {
         ]
     }
 
+    /// best-first nearest-neighbor search: finds the stored point closest to (lat, lon).
+    /// branches on a min-heap keyed by a cell-to-point lower-bound distance (0 when the query
+    /// point lies inside the cell), descending into the closest unexplored cell first and
+    /// pruning any cell whose lower bound already exceeds the best point distance found so far.
+    pub fn nearest(&'a self, lat: f64, lon: f64) -> &'a dyn TreeNode {
+        let (x, y) = mercator_projection(lat, lon);
+        let (x, y) = self.scaler.scale(x, y);
+
+        let mut heap = BinaryHeap::new();
+        heap.push(HeapEntry {
+            dist: cell_point_distance(self, x, y),
+            candidate: Candidate::Cell(self),
+        });
+
+        let mut best: Option<(f64, &'a dyn TreeNode)> = None;
+
+        while let Some(HeapEntry { dist, candidate }) = heap.pop() {
+            if let Some((best_dist, _)) = best {
+                if dist >= best_dist {
+                    break; // every remaining entry has an even larger lower bound
+                }
+            }
+            match candidate {
+                Candidate::Cell(cell) => {
+                    for child in cell.children.iter() {
+                        heap.push(HeapEntry {
+                            dist: cell_point_distance(child, x, y),
+                            candidate: Candidate::Cell(child),
+                        });
+                    }
+                    for &point in &cell.data {
+                        heap.push(HeapEntry {
+                            dist: cell.point_scaled_distance(point, x, y),
+                            candidate: Candidate::Point(point),
+                        });
+                    }
+                }
+                Candidate::Point(point) => {
+                    if best.map_or(true, |(best_dist, _)| dist < best_dist) {
+                        best = Some((dist, point));
+                    }
+                }
+            }
+        }
+
+        best.map(|(_, point)| point)
+            .expect("nearest() called on an empty tree")
+    }
+
+    /// returns every stored point within `meters` of (lat, lon). `MinMaxScaler` scales x and y
+    /// independently (`scale_x != scale_y` in general), so a single east-probe radius would be
+    /// wrong along the north-south axis; instead an east probe and a north probe are projected
+    /// and scaled separately, giving a per-axis radius (`rx`, `ry`), and a point is accepted iff
+    /// it falls inside the resulting ellipse. cell pruning still uses a conservative isotropic
+    /// bound (`rx.max(ry)`, never smaller than the true per-axis radius) so no cell holding a
+    /// real match is skipped; only the final per-point test needs to be elliptical.
+    pub fn within_radius(&'a self, lat: f64, lon: f64, meters: f64) -> Vec<&'a dyn TreeNode> {
+        let (x, y) = mercator_projection(lat, lon);
+        let (x, y) = self.scaler.scale(x, y);
+
+        const METERS_PER_DEGREE: f64 = 111_320.0;
+        let lon_per_meter_degrees = 1.0 / (METERS_PER_DEGREE * lat.to_radians().cos().max(1e-9));
+        let lat_per_meter_degrees = 1.0 / METERS_PER_DEGREE;
+
+        let (ex, ey) = mercator_projection(lat, lon + meters * lon_per_meter_degrees);
+        let (ex, ey) = self.scaler.scale(ex, ey);
+        let rx = Self::point_distance((x, y), (ex, ey)).max(1e-15);
+
+        let (nx, ny) = mercator_projection(lat + meters * lat_per_meter_degrees, lon);
+        let (nx, ny) = self.scaler.scale(nx, ny);
+        let ry = Self::point_distance((x, y), (nx, ny)).max(1e-15);
+
+        let prune_radius = rx.max(ry);
+
+        let mut result = Vec::new();
+        self.collect_within_radius(x, y, rx, ry, prune_radius, &mut result);
+        result
+    }
+
+    /// recursively collects stored points within the ellipse of radii (`rx`, `ry`) (scaled
+    /// space) of (x, y), pruning any cell whose lower-bound distance already exceeds
+    /// `prune_radius`
+    fn collect_within_radius(
+        &'a self,
+        x: f64,
+        y: f64,
+        rx: f64,
+        ry: f64,
+        prune_radius: f64,
+        out: &mut Vec<&'a dyn TreeNode>,
+    ) {
+        if cell_point_distance(self, x, y) > prune_radius {
+            return;
+        }
+        for child in self.children.iter() {
+            child.collect_within_radius(x, y, rx, ry, prune_radius, out);
+        }
+        for &point in &self.data {
+            if self.point_within_ellipse(point, x, y, rx, ry) {
+                out.push(point);
+            }
+        }
+    }
+
+    /// whether `point` (reprojected and rescaled through the same pipeline used to build the
+    /// tree) falls inside the axis-aligned ellipse of radii (`rx`, `ry`) centered at (x, y)
+    fn point_within_ellipse(&self, point: &dyn TreeNode, x: f64, y: f64, rx: f64, ry: f64) -> bool {
+        let (px, py) = mercator_projection(point.lat(), point.lon());
+        let (px, py) = self.scaler.scale(px, py);
+        let dx = (px - x) / rx;
+        let dy = (py - y) / ry;
+        dx * dx + dy * dy <= 1.0
+    }
+
+    /// distance from (x, y) (already in this cell's scaled space) to `point`, reprojecting and
+    /// rescaling the point's lat/lon through the same pipeline used to build the tree
+    fn point_scaled_distance(&self, point: &dyn TreeNode, x: f64, y: f64) -> f64 {
+        let (px, py) = mercator_projection(point.lat(), point.lon());
+        let (px, py) = self.scaler.scale(px, py);
+        Self::point_distance((x, y), (px, py))
+    }
+
     /// creates a new tree with max depth
     pub fn new(data: Vec<&'a dyn TreeNode>, maxdepth: usize) -> Self {
         // 1. mercator project points
@@ -271,7 +517,7 @@ impl<'a> QuadTree<'a> {
             entries_scaled,
             maxdepth,
             0,
-            "".to_string(),
+            CellId::ROOT,
             scaler,
         )
     }
@@ -285,7 +531,7 @@ impl<'a> QuadTree<'a> {
         data: Vec<Entry<'a>>,
         maxdepth: usize,
         current_depth: usize,
-        id: String,
+        id: CellId,
         scaler: MinMaxScaler,
     ) -> Self {
         let mut children = Children::empty();
@@ -317,66 +563,94 @@ impl<'a> QuadTree<'a> {
                 }
             }
 
-            // if there is data for a child cell, recursive call to create that child cell
-            if topleft_data.len() > 0 {
-                let topleft_id = "a".to_string() + &id; // the cell's id
-                let node = QuadTree::new_cell(
-                    xmin,
-                    xhalf,
-                    yhalf,
-                    ymax,
-                    topleft_data,
-                    maxdepth,
-                    current_depth + 1,
-                    topleft_id,
-                    scaler.clone(),
-                );
-                children.a = Some(Box::new(node)); // save subtree as child
-            }
-            if topright_data.len() > 0 {
-                let topright_id = "b".to_string() + &id;
-                let node = QuadTree::new_cell(
-                    xhalf,
-                    xmax,
-                    yhalf,
-                    ymax,
-                    topright_data,
-                    maxdepth,
-                    current_depth + 1,
-                    topright_id,
-                    scaler.clone(),
+            // build each non-empty child. above PARALLEL_BUILD_THRESHOLD points, the four
+            // (disjoint, independently-owned) subtrees are expanded concurrently via a
+            // two-deep `rayon::join` nest; below it a plain sequential build avoids paying
+            // task-spawn overhead for the common near-leaf case.
+            let total_children_data =
+                topleft_data.len() + topright_data.len() + bottomleft_data.len() + bottomright_data.len();
+
+            let build_a = || {
+                (topleft_data.len() > 0).then(|| {
+                    Box::new(QuadTree::new_cell(
+                        xmin,
+                        xhalf,
+                        yhalf,
+                        ymax,
+                        topleft_data,
+                        maxdepth,
+                        current_depth + 1,
+                        id.child(0), // a
+                        scaler.clone(),
+                    ))
+                })
+            };
+            let build_b = || {
+                (topright_data.len() > 0).then(|| {
+                    Box::new(QuadTree::new_cell(
+                        xhalf,
+                        xmax,
+                        yhalf,
+                        ymax,
+                        topright_data,
+                        maxdepth,
+                        current_depth + 1,
+                        id.child(1), // b
+                        scaler.clone(),
+                    ))
+                })
+            };
+            let build_c = || {
+                (bottomleft_data.len() > 0).then(|| {
+                    Box::new(QuadTree::new_cell(
+                        xmin,
+                        xhalf,
+                        ymin,
+                        yhalf,
+                        bottomleft_data,
+                        maxdepth,
+                        current_depth + 1,
+                        id.child(2), // c
+                        scaler.clone(),
+                    ))
+                })
+            };
+            let build_d = || {
+                (bottomright_data.len() > 0).then(|| {
+                    Box::new(QuadTree::new_cell(
+                        xhalf,
+                        xmax,
+                        ymin,
+                        yhalf,
+                        bottomright_data,
+                        maxdepth,
+                        current_depth + 1,
+                        id.child(3), // d
+                        scaler.clone(),
+                    ))
+                })
+            };
+
+            if total_children_data > PARALLEL_BUILD_THRESHOLD {
+                let ((a, b), (c, d)) = rayon::join(
+                    || rayon::join(build_a, build_b),
+                    || rayon::join(build_c, build_d),
                 );
-                children.b = Some(Box::new(node));
+                children.a = a;
+                children.b = b;
+                children.c = c;
+                children.d = d;
+            } else {
+                children.a = build_a();
+                children.b = build_b();
+                children.c = build_c();
+                children.d = build_d();
             }
-            if bottomleft_data.len() > 0 {
-                let bottomleft_id = "c".to_string() + &id;
-                let node = QuadTree::new_cell(
-                    xmin,
-                    xhalf,
-                    ymin,
-                    yhalf,
-                    bottomleft_data,
-                    maxdepth,
-                    current_depth + 1,
-                    bottomleft_id,
-                    scaler.clone(),
-                );
-                children.c = Some(Box::new(node));
-            }
-            if bottomright_data.len() > 0 {
-                let bottomright_id = "d".to_string() + &id;
-                let node = QuadTree::new_cell(
-                    xhalf,
-                    xmax,
-                    ymin,
-                    yhalf,
-                    bottomright_data,
-                    maxdepth,
-                    current_depth + 1,
-                    bottomright_id,
-                    scaler.clone(),
-                );
-                children.d = Some(Box::new(node));
+            // union of every child's node-id bitmap, giving this cell's full subtree set in O(1)
+            // cardinality afterwards instead of recursively walking `data` on every query
+            let mut bitmap = RoaringBitmap::new();
+            for c in children.iter() {
+                bitmap.extend(c.bitmap.iter());
             }
             // return this cell
             Self {
@@ -387,7 +661,9 @@ impl<'a> QuadTree<'a> {
                 children,
                 data: vec![],
                 scaler: scaler,
+                id_str: id.to_string(),
                 id,
+                bitmap,
             }
         } else {
             // do not create children. this node is a leaf.
@@ -395,6 +671,7 @@ impl<'a> QuadTree<'a> {
                 data.into_iter().map(|Entry { point, .. }| point).collect();
             // sort this leaf's data (graph nodes) by level
             points.sort_by(|a, b| a.level().cmp(&b.level()).reverse());
+            let bitmap: RoaringBitmap = points.iter().map(|p| u32::from(p.id())).collect();
             // return this cell.
             Self {
                 xmin,
@@ -404,10 +681,18 @@ impl<'a> QuadTree<'a> {
                 children,
                 data: points,
                 scaler: scaler,
+                id_str: id.to_string(),
                 id,
+                bitmap,
             }
         }
     }
+
+    /// node ids of every point in this cell's subtree - an O(1)-cardinality, set-arithmetic-ready
+    /// view backing WSPD covering/intersection checks (see `wspd_analysis`'s `geometric_error_check`)
+    pub fn node_bitmap(&self) -> &RoaringBitmap {
+        &self.bitmap
+    }
 }
 
 // impl Tree Trait for WSPD
@@ -418,7 +703,7 @@ impl<'a> Tree<'a> for QuadTree<'a> {
     }
 
     fn id(&self) -> &str {
-        &self.id
+        &self.id_str
     }
 
     fn diameter(&self) -> f64 {
@@ -437,18 +722,69 @@ impl<'a> Tree<'a> for QuadTree<'a> {
     }
 }
 
+/// one entry on `nearest`'s branch-and-bound frontier: either an unexplored
+/// cell (ranked by a lower-bound distance) or an actual stored point (ranked by its exact,
+/// reprojected distance)
+enum Candidate<'a> {
+    Cell(&'a QuadTree<'a>),
+    Point(&'a dyn TreeNode),
+}
+
+struct HeapEntry<'a> {
+    dist: f64,
+    candidate: Candidate<'a>,
+}
+
+impl<'a> PartialEq for HeapEntry<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+impl<'a> Eq for HeapEntry<'a> {}
+impl<'a> PartialOrd for HeapEntry<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<'a> Ord for HeapEntry<'a> {
+    // reversed so BinaryHeap (a max-heap) pops the smallest distance first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist.partial_cmp(&self.dist).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// lower-bound distance from (x, y) (scaled space) to `cell`: 0 if the point lies inside the
+/// cell, otherwise the distance to the nearest edge/corner
+fn cell_point_distance(cell: &QuadTree, x: f64, y: f64) -> f64 {
+    let dx = if x < cell.xmin {
+        cell.xmin - x
+    } else if x > cell.xmax {
+        x - cell.xmax
+    } else {
+        0.0
+    };
+    let dy = if y < cell.ymin {
+        cell.ymin - y
+    } else if y > cell.ymax {
+        y - cell.ymax
+    } else {
+        0.0
+    };
+    (dx * dx + dy * dy).sqrt()
+}
+
 /// maps from (lat φ, lon λ) to (x,y)
-fn mercator_projection(φ: f64, λ: f64) -> (f64, f64) {
+pub(crate) fn mercator_projection(φ: f64, λ: f64) -> (f64, f64) {
     (λ.to_radians(), φ.to_radians().sin().atanh())
 }
 
 /// maps from (x,y) to (lat φ, lon λ)
-fn inverse_mercator_projection((x, y): (f64, f64)) -> (f64, f64) {
+pub(crate) fn inverse_mercator_projection((x, y): (f64, f64)) -> (f64, f64) {
     (y.sinh().atan().to_degrees(), x.to_degrees())
 }
 
 #[derive(Clone, Debug)]
-struct MinMaxScaler {
+pub(crate) struct MinMaxScaler {
     x_max: f64,
     x_min: f64,
     y_max: f64,
@@ -458,21 +794,26 @@ struct MinMaxScaler {
 impl MinMaxScaler {
     /// creates a scaler from a dataset
     pub fn from(data: &[Entry]) -> Self {
+        Self::from_xy(&data.iter().map(|d| (d.x, d.y)).collect::<Vec<_>>())
+    }
+
+    /// creates a scaler from a set of plain (x,y) coordinates
+    pub(crate) fn from_xy(data: &[(f64, f64)]) -> Self {
         let (mut x_max, mut x_min) = (f64::MIN, f64::MAX);
         let (mut y_max, mut y_min) = (f64::MIN, f64::MAX);
 
-        for d in data {
-            if d.x > x_max {
-                x_max = d.x;
+        for &(x, y) in data {
+            if x > x_max {
+                x_max = x;
             }
-            if d.x < x_min {
-                x_min = d.x;
+            if x < x_min {
+                x_min = x;
             }
-            if d.y > y_max {
-                y_max = d.y;
+            if y > y_max {
+                y_max = y;
             }
-            if d.y < y_min {
-                y_min = d.y;
+            if y < y_min {
+                y_min = y;
             }
         }
 
@@ -1,9 +1,12 @@
-use crate::graph::{BaseNode, CHEdge, CHGraph};
+use crate::dijkstra::Dijkstra;
+use crate::graph::{BaseNode, CHEdge, CHGraph, GeoNode, NodeId};
+use crate::landmarks::haversine_meters;
 use crate::paths::SourceTargetPair;
 
 use rand::distributions::{Distribution, Uniform};
 use rand::rngs::StdRng;
 use rand::SeedableRng;
+use rstar::{PointDistance, RTree, RTreeObject, AABB};
 
 use std::io::{stdout, Write};
 
@@ -50,6 +53,274 @@ impl Iterator for STPGenerator {
     }
 }
 
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// a node's coordinate projected to a local equirectangular approximation in meters, centered on
+/// the dataset's mean latitude - accurate enough over a single road network's extent to drive
+/// `rstar`'s range query. the final accept/reject decision against the requested distance band is
+/// made with the exact `haversine_meters` distance, so projection error can't bias the sample.
+struct GeoPoint {
+    index: usize,
+    xy: [f64; 2],
+}
+
+impl RTreeObject for GeoPoint {
+    type Envelope = AABB<[f64; 2]>;
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point(self.xy)
+    }
+}
+
+impl PointDistance for GeoPoint {
+    fn distance_2(&self, point: &[f64; 2]) -> f64 {
+        let dx = self.xy[0] - point[0];
+        let dy = self.xy[1] - point[1];
+        dx * dx + dy * dy
+    }
+}
+
+fn equirect_meters(lat: f64, lon: f64, lat0_rad: f64) -> [f64; 2] {
+    [
+        EARTH_RADIUS_M * lon.to_radians() * lat0_rad.cos(),
+        EARTH_RADIUS_M * lat.to_radians(),
+    ]
+}
+
+/// samples source/target pairs with the target drawn from a requested great-circle distance band
+/// `[dist_min, dist_max]` meters around the source, instead of `STPGenerator`'s uniform sampling -
+/// which over-samples long cross-graph trips and under-samples short local ones. builds an
+/// `rstar` R-tree over the graph's node coordinates once; each draw picks a source uniformly and
+/// range-queries the tree for every node within `dist_max` (`rstar`'s circular range query),
+/// keeps the ones also >= `dist_min` away by exact great-circle distance, and picks uniformly
+/// among the survivors.
+pub struct SpatialSTPGenerator {
+    rng: StdRng,
+    node_dist: Uniform<usize>,
+    coords: Vec<(f64, f64)>, // (lat, lon), indexed by plain node index
+    tree: RTree<GeoPoint>,
+    lat0_rad: f64,
+    dist_min: f64,
+    dist_max: f64,
+    n: usize,
+    n_max: usize,
+}
+
+impl SpatialSTPGenerator {
+    pub fn new<N, E>(
+        graph: &dyn CHGraph<Node = N, Edge = E>,
+        seed: Option<u64>,
+        n: usize,
+        dist_min: f64,
+        dist_max: f64,
+    ) -> Self
+    where
+        N: BaseNode + GeoNode,
+        E: CHEdge,
+    {
+        let coords: Vec<(f64, f64)> = graph.iter_nodes().map(|nd| (nd.lat(), nd.lon())).collect();
+        let lat0_rad = (coords.iter().map(|&(lat, _)| lat).sum::<f64>() / coords.len() as f64)
+            .to_radians();
+
+        let points: Vec<GeoPoint> = coords
+            .iter()
+            .enumerate()
+            .map(|(index, &(lat, lon))| GeoPoint {
+                index,
+                xy: equirect_meters(lat, lon, lat0_rad),
+            })
+            .collect();
+
+        Self {
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            node_dist: Uniform::from(0..coords.len()),
+            tree: RTree::bulk_load(points),
+            lat0_rad,
+            coords,
+            dist_min,
+            dist_max,
+            n: 0,
+            n_max: n,
+        }
+    }
+
+    /// draws one source/target pair, or `None` if no node fell within the requested distance
+    /// band of the drawn source
+    pub fn generate(&mut self) -> Option<SourceTargetPair> {
+        self.n += 1;
+        let source_idx = self.node_dist.sample(&mut self.rng);
+        let (src_lat, src_lon) = self.coords[source_idx];
+        let src_xy = equirect_meters(src_lat, src_lon, self.lat0_rad);
+
+        let candidates: Vec<usize> = self
+            .tree
+            .locate_within_distance(src_xy, self.dist_max * self.dist_max)
+            .map(|p| p.index)
+            .filter(|&idx| {
+                if idx == source_idx {
+                    return false;
+                }
+                let (lat, lon) = self.coords[idx];
+                let d = f64::from(haversine_meters(src_lat, src_lon, lat, lon));
+                d >= self.dist_min && d <= self.dist_max
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let target_idx = candidates[Uniform::from(0..candidates.len()).sample(&mut self.rng)];
+        Some(SourceTargetPair {
+            source: source_idx.into(),
+            target: target_idx.into(),
+        })
+    }
+}
+
+impl Iterator for SpatialSTPGenerator {
+    type Item = Option<SourceTargetPair>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n >= self.n_max {
+            None
+        } else {
+            Some(self.generate())
+        }
+    }
+}
+
+/// generator struct that can be iterated for n_max random waypoint tuples of size `k`, for
+/// multi-waypoint path generation (see `Dijkstra::waypoint_search`). unlike `STPGenerator`, which
+/// is hardcoded to pairs, each item here is a `Vec<NodeId>` of length `k`.
+pub struct WaypointGenerator {
+    rng: StdRng,
+    dist: Uniform<usize>,
+    k: usize,
+    n: usize,
+    n_max: usize,
+}
+
+impl WaypointGenerator {
+    pub fn new(max: usize, seed: Option<u64>, n: usize, k: usize) -> Self {
+        Self {
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            dist: Uniform::from(0..max),
+            k,
+            n: 0,
+            n_max: n,
+        }
+    }
+
+    pub fn generate(&mut self) -> Vec<NodeId> {
+        self.n += 1;
+        (0..self.k).map(|_| self.dist.sample(&mut self.rng).into()).collect()
+    }
+}
+
+impl Iterator for WaypointGenerator {
+    type Item = Vec<NodeId>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n >= self.n_max {
+            None
+        } else {
+            Some(self.generate())
+        }
+    }
+}
+
+/// one `(source, target)` pair produced by `DijkstraRankGenerator`, tagged with the Dijkstra
+/// rank `2^k` of `target` as settled from `source`
+pub struct DijkstraRankPair {
+    pub pair: SourceTargetPair,
+    pub rank: usize,
+}
+
+/// samples query pairs bucketed by Dijkstra rank instead of drawing both endpoints uniformly at
+/// random, which mostly produces trivial long-distance queries. for a uniformly-random source
+/// `s`, runs a single-source Dijkstra settling nodes in non-decreasing distance order and, for
+/// each `k` in `[k_min, floor(log2(settled_count - 1))]`, pairs `s` with the node settled at
+/// position `2^k`, tagged with rank `2^k`. sources with a small reachable set simply yield fewer
+/// buckets. iterates like `STPGenerator`, seeded the same way for reproducibility.
+pub struct DijkstraRankGenerator<'a, N, E> {
+    dijkstra: Dijkstra<'a, N, E>,
+    nodes: Uniform<usize>,
+    rng: StdRng,
+    k_min: u32,
+    n_sources: usize,
+    sources_drawn: usize,
+    pending: std::vec::IntoIter<DijkstraRankPair>,
+}
+
+impl<'a, N: BaseNode, E: CHEdge> DijkstraRankGenerator<'a, N, E> {
+    pub fn new(
+        graph: &'a dyn CHGraph<Node = N, Edge = E>,
+        n_sources: usize,
+        k_min: u32,
+        seed: Option<u64>,
+    ) -> Self {
+        Self {
+            dijkstra: Dijkstra::new(graph),
+            nodes: Uniform::from(0..graph.num_nodes()),
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            k_min,
+            n_sources,
+            sources_drawn: 0,
+            pending: Vec::new().into_iter(),
+        }
+    }
+
+    /// draws the next source, settles the whole graph from it, and buckets the result by rank
+    fn next_source_buckets(&mut self) -> Vec<DijkstraRankPair> {
+        let source: NodeId = self.nodes.sample(&mut self.rng).into();
+        let settled = self.dijkstra.single_source_settle_order(source);
+
+        // settled[0] == source itself; ranks are sampled from the remaining, reachable nodes
+        let max_index = settled.len().saturating_sub(1);
+        if max_index == 0 {
+            return Vec::new();
+        }
+        let max_k = (max_index as f64).log2().floor() as u32;
+
+        (self.k_min..=max_k)
+            .map(|k| {
+                let rank = 1usize << k;
+                DijkstraRankPair {
+                    pair: SourceTargetPair {
+                        source,
+                        target: settled[rank],
+                    },
+                    rank,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a, N: BaseNode, E: CHEdge> Iterator for DijkstraRankGenerator<'a, N, E> {
+    type Item = DijkstraRankPair;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(pair) = self.pending.next() {
+                return Some(pair);
+            }
+            if self.sources_drawn >= self.n_sources {
+                return None;
+            }
+            self.sources_drawn += 1;
+            self.pending = self.next_source_buckets().into_iter();
+        }
+    }
+}
+
 /// generates random point pairs
 pub fn generate_pairs<N: BaseNode, E: CHEdge>(
     graph: &dyn CHGraph<Node = N, Edge = E>,
@@ -0,0 +1,254 @@
+use std::hash::{Hash, Hasher};
+
+use rstar::{Envelope, AABB};
+
+use crate::{
+    quadtree::{mercator_projection, MinMaxScaler, PointContainer, TreeNode},
+    wspd::{Distance, Tree},
+};
+
+/// branching factor used when tiling leaves into parents (and parents into grandparents, etc.)
+/// in `str_pack_nodes`, matching `rstar`'s default fanout. leaves themselves always hold exactly
+/// one point regardless of this constant - see `str_pack_leaves`.
+const NODE_CAPACITY: usize = 6;
+
+/// a single projected+scaled point awaiting STR packing
+#[derive(Clone, Copy)]
+struct STRPoint<'a> {
+    point: &'a dyn TreeNode,
+    xy: [f64; 2],
+}
+
+/// a hand-rolled, Sort-Tile-Recursive bulk-loaded R-tree. unlike [`crate::rtree::RTree`], which
+/// delegates bulk-loading to `rstar::RTree::bulk_load`, this packs leaves and internal nodes
+/// itself: sort by x into `⌈√(N/M)⌉` vertical slabs of `⌈√(N·M)⌉` points each, sort every slab by
+/// y, and pack one leaf per point; then repeat the same slab-and-pack process one level up over
+/// the node MBRs, grouping `M` nodes per parent, until a single root remains. every node keeps
+/// the tight min/max bounding rectangle of its subtree, so `diameter()` reflects the real
+/// rectangle extent instead of a quadtree's forced square cells.
+pub struct RStarTree<'a> {
+    children: Vec<RStarTree<'a>>,
+    data: Vec<&'a dyn TreeNode>,
+    bbox: AABB<[f64; 2]>,
+    scaler: MinMaxScaler,
+    id: String,
+}
+
+impl<'a> PartialEq for RStarTree<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+impl<'a> Eq for RStarTree<'a> {}
+impl<'a> Hash for RStarTree<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<'a> Distance for RStarTree<'a> {
+    fn distance(&self, other: &Self) -> f64 {
+        let (lo_a, hi_a) = (self.bbox.lower(), self.bbox.upper());
+        let (lo_b, hi_b) = (other.bbox.lower(), other.bbox.upper());
+
+        // axis-aligned gap between the two boxes on each axis (0 if they overlap on that axis)
+        let dx = (lo_a[0] - hi_b[0]).max(lo_b[0] - hi_a[0]).max(0.0);
+        let dy = (lo_a[1] - hi_b[1]).max(lo_b[1] - hi_a[1]).max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
+}
+
+impl<'a> Tree<'a> for RStarTree<'a> {
+    type Iter = std::slice::Iter<'a, RStarTree<'a>>;
+
+    fn children(&'a self) -> Self::Iter {
+        self.children.iter()
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn diameter(&self) -> f64 {
+        if self.data.len() == 1 {
+            0.0
+        } else {
+            let lo = self.bbox.lower();
+            let hi = self.bbox.upper();
+            ((hi[0] - lo[0]).powi(2) + (hi[1] - lo[1]).powi(2)).sqrt()
+        }
+    }
+}
+
+impl<'a> RStarTree<'a> {
+    /// builds an `RStarTree` over `data` by STR bulk-loading the projected/scaled points.
+    pub fn new(data: Vec<&'a dyn TreeNode>) -> Self {
+        let projected: Vec<(f64, f64)> = data
+            .iter()
+            .map(|p| mercator_projection(p.lat(), p.lon()))
+            .collect();
+        let scaler = MinMaxScaler::from_xy(&projected);
+
+        let points: Vec<STRPoint<'a>> = data
+            .iter()
+            .zip(projected.iter())
+            .map(|(&point, &(x, y))| {
+                let (x, y) = scaler.scale(x, y);
+                STRPoint { point, xy: [x, y] }
+            })
+            .collect();
+
+        let leaves = Self::str_pack_leaves(points, &scaler);
+        Self::str_pack_nodes(leaves, &scaler)
+    }
+
+    /// packs the leaf level: sorts points by x into `⌈√(N/M)⌉` vertical slabs of `⌈√(N·M)⌉`
+    /// points each (the same STR tiling `NODE_CAPACITY` drives one level up in
+    /// `str_pack_nodes`), sorts every slab by y, then makes one leaf per point. leaves must hold
+    /// exactly one point: `WSPD::alg_wspd` only recurses into `children()` when a node isn't
+    /// well-separated, so a multi-point leaf (`diameter() > 0`, no children) would silently drop
+    /// every point pair inside it from the decomposition instead of separating them - the same
+    /// single-point-leaf invariant `RTree` gets for free from `rstar::RTree::bulk_load`.
+    fn str_pack_leaves(mut points: Vec<STRPoint<'a>>, scaler: &MinMaxScaler) -> Vec<RStarTree<'a>> {
+        let n = points.len();
+        if n <= 1 {
+            return points
+                .into_iter()
+                .map(|p| Self::make_leaf(vec![p], scaler, String::new()))
+                .collect();
+        }
+
+        let num_leaves = (n as f64 / NODE_CAPACITY as f64).ceil();
+        let slab_count = num_leaves.sqrt().ceil().max(1.0) as usize;
+        let slab_size = ((n as f64) / (slab_count as f64)).ceil().max(1.0) as usize;
+
+        points.sort_by(|a, b| a.xy[0].partial_cmp(&b.xy[0]).unwrap());
+
+        let mut leaves = Vec::new();
+        for (slab_idx, slab) in points.chunks(slab_size).enumerate() {
+            let mut slab = slab.to_vec();
+            slab.sort_by(|a, b| a.xy[1].partial_cmp(&b.xy[1]).unwrap());
+            for (leaf_idx, point) in slab.into_iter().enumerate() {
+                let id = format!("{}.{}", slab_idx, leaf_idx);
+                leaves.push(Self::make_leaf(vec![point], scaler, id));
+            }
+        }
+        leaves
+    }
+
+    /// builds the internal levels above the leaves with the same STR slab-and-pack process,
+    /// keying slabs on each node's bounding-box center instead of a raw point, and repeats
+    /// bottom-up until a single root remains.
+    fn str_pack_nodes(mut nodes: Vec<RStarTree<'a>>, scaler: &MinMaxScaler) -> RStarTree<'a> {
+        if nodes.len() == 1 {
+            return nodes.pop().unwrap();
+        }
+
+        let n = nodes.len();
+        let num_parents = (n as f64 / NODE_CAPACITY as f64).ceil();
+        let slab_count = num_parents.sqrt().ceil().max(1.0) as usize;
+        let slab_size = ((n as f64) / (slab_count as f64)).ceil().max(1.0) as usize;
+
+        nodes.sort_by(|a, b| center(a.bbox)[0].partial_cmp(&center(b.bbox)[0]).unwrap());
+
+        let mut node_iter = nodes.into_iter();
+        let mut parents = Vec::new();
+        let mut slab_idx = 0;
+        loop {
+            let mut slab: Vec<RStarTree<'a>> = node_iter.by_ref().take(slab_size).collect();
+            if slab.is_empty() {
+                break;
+            }
+            slab.sort_by(|a, b| center(a.bbox)[1].partial_cmp(&center(b.bbox)[1]).unwrap());
+
+            let mut child_iter = slab.into_iter();
+            let mut leaf_idx = 0;
+            loop {
+                let group: Vec<RStarTree<'a>> = child_iter.by_ref().take(NODE_CAPACITY).collect();
+                if group.is_empty() {
+                    break;
+                }
+                let id = format!("{}.{}", slab_idx, leaf_idx);
+                parents.push(Self::make_parent(group, scaler, id));
+                leaf_idx += 1;
+            }
+            slab_idx += 1;
+        }
+
+        Self::str_pack_nodes(parents, scaler)
+    }
+
+    fn make_leaf(points: Vec<STRPoint<'a>>, scaler: &MinMaxScaler, id: String) -> RStarTree<'a> {
+        let bbox = points
+            .iter()
+            .map(|p| AABB::from_point(p.xy))
+            .reduce(merge_bbox)
+            .expect("leaf must have at least one point");
+        let data = points.into_iter().map(|p| p.point).collect();
+        Self {
+            children: Vec::new(),
+            data,
+            bbox,
+            scaler: scaler.clone(),
+            id,
+        }
+    }
+
+    fn make_parent(
+        children: Vec<RStarTree<'a>>,
+        scaler: &MinMaxScaler,
+        id: String,
+    ) -> RStarTree<'a> {
+        let bbox = children
+            .iter()
+            .map(|c| c.bbox)
+            .reduce(merge_bbox)
+            .expect("parent must have at least one child");
+        Self {
+            children,
+            data: Vec::new(),
+            bbox,
+            scaler: scaler.clone(),
+            id,
+        }
+    }
+
+    /// number of points stored in this subtree
+    pub fn size(&self) -> usize {
+        self.children.iter().fold(0, |acc, c| acc + c.size()) + self.data.len()
+    }
+
+    /// iterates all points in this tree
+    pub fn points(&'a self) -> Box<dyn Iterator<Item = &&'a dyn TreeNode> + 'a> {
+        Box::new(
+            self.children
+                .iter()
+                .flat_map(|c| c.points())
+                .chain(&self.data),
+        )
+    }
+}
+
+impl<'a> PointContainer<'a> for RStarTree<'a> {
+    fn points(&'a self) -> Box<dyn Iterator<Item = &&'a dyn TreeNode> + 'a> {
+        RStarTree::points(self)
+    }
+    fn size(&self) -> usize {
+        RStarTree::size(self)
+    }
+}
+
+fn center(bbox: AABB<[f64; 2]>) -> [f64; 2] {
+    let lo = bbox.lower();
+    let hi = bbox.upper();
+    [(lo[0] + hi[0]) / 2.0, (lo[1] + hi[1]) / 2.0]
+}
+
+fn merge_bbox(a: AABB<[f64; 2]>, b: AABB<[f64; 2]>) -> AABB<[f64; 2]> {
+    let (lo_a, hi_a) = (a.lower(), a.upper());
+    let (lo_b, hi_b) = (b.lower(), b.upper());
+    AABB::from_corners(
+        [lo_a[0].min(lo_b[0]), lo_a[1].min(lo_b[1])],
+        [hi_a[0].max(hi_b[0]), hi_a[1].max(hi_b[1])],
+    )
+}
@@ -0,0 +1,159 @@
+use std::hash::{Hash, Hasher};
+
+use rstar::{Envelope, RTree as RStarRTree, RTreeNode, RTreeObject, AABB};
+
+use crate::{
+    quadtree::{mercator_projection, MinMaxScaler, PointContainer, TreeNode},
+    wspd::{Distance, Tree},
+};
+
+/// a single projected+scaled point, indexable by `rstar`
+struct GeoPoint<'a> {
+    point: &'a dyn TreeNode,
+    xy: [f64; 2],
+}
+
+impl<'a> RTreeObject for GeoPoint<'a> {
+    type Envelope = AABB<[f64; 2]>;
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point(self.xy)
+    }
+}
+
+/// a node of an R*-tree, bulk-loaded with the `rstar` crate and materialized into an owned
+/// recursive structure so it can implement `wspd::Tree` the same way `QuadTree` does.
+pub struct RTree<'a> {
+    children: Vec<RTree<'a>>,
+    data: Vec<&'a dyn TreeNode>,
+    bbox: AABB<[f64; 2]>,
+    scaler: MinMaxScaler,
+    id: String,
+}
+
+impl<'a> PartialEq for RTree<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+impl<'a> Eq for RTree<'a> {}
+impl<'a> Hash for RTree<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<'a> Distance for RTree<'a> {
+    fn distance(&self, other: &Self) -> f64 {
+        let (lo_a, hi_a) = (self.bbox.lower(), self.bbox.upper());
+        let (lo_b, hi_b) = (other.bbox.lower(), other.bbox.upper());
+
+        // axis-aligned gap between the two boxes on each axis (0 if they overlap on that axis)
+        let dx = (lo_a[0] - hi_b[0]).max(lo_b[0] - hi_a[0]).max(0.0);
+        let dy = (lo_a[1] - hi_b[1]).max(lo_b[1] - hi_a[1]).max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
+}
+
+impl<'a> Tree<'a> for RTree<'a> {
+    type Iter = std::slice::Iter<'a, RTree<'a>>;
+
+    fn children(&'a self) -> Self::Iter {
+        self.children.iter()
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn diameter(&self) -> f64 {
+        if self.data.len() == 1 {
+            0.0
+        } else {
+            let lo = self.bbox.lower();
+            let hi = self.bbox.upper();
+            ((hi[0] - lo[0]).powi(2) + (hi[1] - lo[1]).powi(2)).sqrt()
+        }
+    }
+}
+
+impl<'a> RTree<'a> {
+    /// builds an R*-tree over `data` by bulk-loading projected/scaled points with `rstar`,
+    /// then walking the resulting node hierarchy once into an owned tree of `RTree` nodes.
+    pub fn new(data: Vec<&'a dyn TreeNode>) -> Self {
+        let projected: Vec<(f64, f64)> = data
+            .iter()
+            .map(|p| mercator_projection(p.lat(), p.lon()))
+            .collect();
+        let scaler = MinMaxScaler::from_xy(&projected);
+
+        let points: Vec<GeoPoint<'a>> = data
+            .iter()
+            .zip(projected.iter())
+            .map(|(&point, &(x, y))| {
+                let (x, y) = scaler.scale(x, y);
+                GeoPoint { point, xy: [x, y] }
+            })
+            .collect();
+
+        let tree = RStarRTree::bulk_load(points);
+        Self::from_rstar_node(&RTreeNode::Parent(tree.root().clone()), String::new(), &scaler)
+    }
+
+    /// recursively materializes one `rstar` node (and its subtree) into an owned `RTree`
+    fn from_rstar_node(
+        node: &RTreeNode<GeoPoint<'a>>,
+        id: String,
+        scaler: &MinMaxScaler,
+    ) -> Self {
+        match node {
+            RTreeNode::Leaf(point) => Self {
+                children: Vec::new(),
+                data: vec![point.point],
+                bbox: point.envelope(),
+                scaler: scaler.clone(),
+                id,
+            },
+            RTreeNode::Parent(parent) => {
+                let children: Vec<RTree<'a>> = parent
+                    .children()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, child)| {
+                        Self::from_rstar_node(child, format!("{}{}", i, id), scaler)
+                    })
+                    .collect();
+                Self {
+                    bbox: parent.envelope(),
+                    data: Vec::new(),
+                    children,
+                    scaler: scaler.clone(),
+                    id,
+                }
+            }
+        }
+    }
+
+    /// number of points stored in this subtree
+    pub fn size(&self) -> usize {
+        self.children.iter().fold(0, |acc, c| acc + c.size()) + self.data.len()
+    }
+
+    /// iterates all points in this tree
+    pub fn points(&'a self) -> Box<dyn Iterator<Item = &&'a dyn TreeNode> + 'a> {
+        Box::new(
+            self.children
+                .iter()
+                .flat_map(|c| c.points())
+                .chain(&self.data),
+        )
+    }
+}
+
+impl<'a> PointContainer<'a> for RTree<'a> {
+    fn points(&'a self) -> Box<dyn Iterator<Item = &&'a dyn TreeNode> + 'a> {
+        RTree::points(self)
+    }
+    fn size(&self) -> usize {
+        RTree::size(self)
+    }
+}
@@ -1,8 +1,14 @@
+mod dotbuilder;
 mod drawmap;
 mod geojsonbuilder;
+mod geozerobuilder;
+mod polylinebuilder;
 
+pub use dotbuilder::DotBuilder;
 pub use drawmap::MapBuilder;
 pub use geojsonbuilder::GeoJsonBuilder;
+pub use geozerobuilder::GeoZeroBuilder;
+pub use polylinebuilder::PolylineBuilder;
 use rand::Rng;
 
 use crate::{graph::NodeId, paths::EdgeList};
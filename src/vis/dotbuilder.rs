@@ -0,0 +1,142 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{
+    graph::{BaseEdge, BaseNode, GeoGraph, GeoNode, NodeId},
+    paths::EdgeList,
+};
+
+use super::{Color, VisBuilder};
+
+/// one node statement: a stable dot identifier plus the attributes dot should render it with.
+/// dot tolerates the same id being declared more than once (later attributes just win), so
+/// callers don't need to dedup before pushing.
+struct DotNode {
+    id: String,
+    lon: f64,
+    lat: f64,
+    color: Option<Color>,
+}
+
+struct DotEdge {
+    from: String,
+    to: String,
+    color: Color,
+    is_path: bool,
+}
+
+/// renders `point`/`line`/`path` calls as GraphViz DOT text - like petgraph's `Dot`/`Config` -
+/// instead of `MapBuilder`'s raster map, useful for inspecting a small subgraph, a hitting-set,
+/// or a handful of CH paths in an interactive viewer. nodes carry `pos="lon,lat!"` so `neato`
+/// lays the graph out geographically; edges drawn by `path` are tagged `style=bold` so a CH path
+/// is visually distinguishable from a plain `line`/base edge.
+pub struct DotBuilder<'a, N, E> {
+    graph: &'a dyn GeoGraph<Node = N, Edge = E>,
+    nodes: Vec<DotNode>,
+    edges: Vec<DotEdge>,
+    next_synthetic: usize,
+}
+
+impl<'a, N: GeoNode + BaseNode, E: BaseEdge> DotBuilder<'a, N, E> {
+    pub fn new(graph: &'a dyn GeoGraph<Node = N, Edge = E>) -> Self {
+        Self {
+            graph,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            next_synthetic: 0,
+        }
+    }
+
+    pub fn path_with_color(&mut self, path: &EdgeList, color: Color) {
+        for edge in path {
+            let source = self.graph.edge(*edge).source();
+            let target = self.graph.edge(*edge).target();
+            let from = self.push_graph_node(source, None);
+            let to = self.push_graph_node(target, None);
+            self.edges.push(DotEdge {
+                from,
+                to,
+                color,
+                is_path: true,
+            });
+        }
+    }
+
+    /// records a node statement for the graph node `id`, returning its dot identifier
+    fn push_graph_node(&mut self, id: NodeId, color: Option<Color>) -> String {
+        let node = GeoGraph::node(self.graph, id);
+        let dot_id = format!("n{}", String::from(id));
+        self.nodes.push(DotNode {
+            id: dot_id.clone(),
+            lon: node.lon(),
+            lat: node.lat(),
+            color,
+        });
+        dot_id
+    }
+
+    /// records a node statement for a synthetic point not backed by a graph node (e.g. one end
+    /// of a raw `line_with_color` call), returning its dot identifier
+    fn push_point(&mut self, lat: f64, lon: f64) -> String {
+        let dot_id = format!("p{}", self.next_synthetic);
+        self.next_synthetic += 1;
+        self.nodes.push(DotNode {
+            id: dot_id.clone(),
+            lon,
+            lat,
+            color: None,
+        });
+        dot_id
+    }
+}
+
+impl<'a, N: GeoNode + BaseNode, E: BaseEdge> VisBuilder for DotBuilder<'a, N, E> {
+    fn save(&mut self, filename: &str) {
+        let file = File::create(filename).expect("error writing file");
+        let mut buf = BufWriter::new(file);
+
+        writeln!(buf, "digraph {{").expect("error writing file");
+        for node in &self.nodes {
+            match node.color {
+                Some(color) => writeln!(
+                    buf,
+                    "  \"{}\" [pos=\"{},{}!\", style=filled, fillcolor=\"#{:02X}{:02X}{:02X}\"];",
+                    node.id, node.lon, node.lat, color.r, color.g, color.b
+                ),
+                None => writeln!(buf, "  \"{}\" [pos=\"{},{}!\"];", node.id, node.lon, node.lat),
+            }
+            .expect("error writing file");
+        }
+        for edge in &self.edges {
+            let path_style = if edge.is_path { ", style=bold" } else { "" };
+            writeln!(
+                buf,
+                "  \"{}\" -> \"{}\" [color=\"#{:02X}{:02X}{:02X}\"{}];",
+                edge.from, edge.to, edge.color.r, edge.color.g, edge.color.b, path_style
+            )
+            .expect("error writing file");
+        }
+        writeln!(buf, "}}").expect("error writing file");
+    }
+
+    fn point_with_color(&mut self, point: NodeId, color: Color) {
+        self.push_graph_node(point, Some(color));
+    }
+
+    fn path(&mut self, path: &EdgeList) {
+        self.path_with_color(path, Color::RED)
+    }
+
+    fn line_with_color(&mut self, from: (f64, f64), to: (f64, f64), color: Color) {
+        let a = self.push_point(from.0, from.1);
+        let b = self.push_point(to.0, to.1);
+        self.edges.push(DotEdge {
+            from: a,
+            to: b,
+            color,
+            is_path: false,
+        });
+    }
+}
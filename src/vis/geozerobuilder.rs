@@ -0,0 +1,160 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use geozero::{
+    error::Result as GeozeroResult, ColumnValue, FeatureProcessor, GeomProcessor, PropertyProcessor,
+};
+
+use crate::{
+    graph::{BaseEdge, BaseNode, GeoGraph, GeoNode, NodeId},
+    paths::EdgeList,
+};
+
+use super::{Color, VisBuilder};
+
+/// one accumulated drawing primitive, kept around in a writer-agnostic form until `save()`
+/// drives it through whichever geozero `FeatureProcessor` matches the output file extension.
+enum Feature {
+    LineString { coords: Vec<(f64, f64)>, stroke: String },
+    Point { coord: (f64, f64), marker_color: String },
+}
+
+/// builds up paths/points/lines as geozero geometries so the same primitives `GeoJsonBuilder`
+/// draws can be serialized to SVG, WKT, CSV or FlatGeobuf - whichever `save()`'s extension asks
+/// for - instead of being hardcoded to GeoJSON.
+pub struct GeoZeroBuilder<'a, N, E> {
+    features: Vec<Feature>,
+    graph: &'a dyn GeoGraph<Node = N, Edge = E>,
+}
+
+impl<'a, N: GeoNode + BaseNode, E: BaseEdge> GeoZeroBuilder<'a, N, E> {
+    pub fn new(graph: &'a dyn GeoGraph<Node = N, Edge = E>) -> Self {
+        Self {
+            features: Vec::new(),
+            graph,
+        }
+    }
+
+    pub fn path_with_color(&mut self, path: &EdgeList, color: Color) {
+        if path.len() == 0 {
+            return;
+        }
+        let mut coords = Vec::with_capacity(path.len() + 1);
+        for edge in path {
+            let start = self.graph.edge(*edge).source();
+            let node = GeoGraph::node(self.graph, start);
+            coords.push((node.lon(), node.lat()));
+        }
+        let last_end = self.graph.edge(*path.last().unwrap()).target();
+        let node = GeoGraph::node(self.graph, last_end);
+        coords.push((node.lon(), node.lat()));
+
+        self.features.push(Feature::LineString {
+            coords,
+            stroke: hex_color(color),
+        });
+    }
+
+    /// drives the accumulated features through a geozero `FeatureProcessor`
+    fn process<P: FeatureProcessor>(&self, processor: &mut P) -> GeozeroResult<()> {
+        processor.dataset_begin(None)?;
+        for (idx, feature) in self.features.iter().enumerate() {
+            let idx = idx as u64;
+            processor.feature_begin(idx)?;
+
+            processor.properties_begin()?;
+            match feature {
+                Feature::LineString { stroke, .. } => {
+                    processor.property(0, "stroke", &ColumnValue::String(stroke))?;
+                }
+                Feature::Point { marker_color, .. } => {
+                    processor.property(0, "marker-color", &ColumnValue::String(marker_color))?;
+                }
+            }
+            processor.properties_end()?;
+
+            processor.geometry_begin()?;
+            match feature {
+                Feature::LineString { coords, .. } => {
+                    processor.linestring_begin(true, coords.len(), 0)?;
+                    for (i, (lon, lat)) in coords.iter().enumerate() {
+                        processor.xy(*lon, *lat, i)?;
+                    }
+                    processor.linestring_end(true, 0)?;
+                }
+                Feature::Point { coord, .. } => {
+                    processor.point_begin(0)?;
+                    processor.xy(coord.0, coord.1, 0)?;
+                    processor.point_end(0)?;
+                }
+            }
+            processor.geometry_end()?;
+
+            processor.feature_end(idx)?;
+        }
+        processor.dataset_end()
+    }
+}
+
+fn hex_color(color: Color) -> String {
+    // format: {:02X} -> two digit with leading zero, hex with uppercase
+    format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b)
+}
+
+impl<'a, N: GeoNode + BaseNode, E: BaseEdge> VisBuilder for GeoZeroBuilder<'a, N, E> {
+    /// dispatches on the file extension to pick a geozero writer: `.svg`, `.wkt`, `.csv` or
+    /// `.fgb`/`.flatgeobuf` for FlatGeobuf
+    fn save(&mut self, filename: &str) {
+        let ext = Path::new(filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        let new_writer = || BufWriter::new(File::create(filename).expect("error writing file"));
+
+        match ext.as_str() {
+            "svg" => {
+                let mut writer = geozero::svg::SvgWriter::new(new_writer(), true);
+                self.process(&mut writer).expect("error writing svg file");
+            }
+            "wkt" => {
+                let mut writer = geozero::wkt::WktWriter::new(new_writer());
+                self.process(&mut writer).expect("error writing wkt file");
+            }
+            "csv" => {
+                let mut writer = geozero::csv::CsvWriter::new(new_writer());
+                self.process(&mut writer).expect("error writing csv file");
+            }
+            "fgb" | "flatgeobuf" => {
+                let mut fgb = geozero::flatgeobuf::FgbWriter::create(
+                    "paths",
+                    geozero::flatgeobuf::FeatureType::Unknown,
+                )
+                .expect("error creating flatgeobuf writer");
+                self.process(&mut fgb).expect("error writing flatgeobuf features");
+                fgb.write(&mut new_writer())
+                    .expect("error flushing flatgeobuf file");
+            }
+            _ => panic!("unsupported vis output extension: .{}", ext),
+        }
+    }
+
+    fn point_with_color(&mut self, point: NodeId, color: Color) {
+        let node = GeoGraph::node(self.graph, point);
+        self.features.push(Feature::Point {
+            coord: (node.lon(), node.lat()),
+            marker_color: hex_color(color),
+        });
+    }
+
+    fn path(&mut self, path: &EdgeList) {
+        self.path_with_color(path, Color::RED)
+    }
+
+    fn line_with_color(&mut self, from: (f64, f64), to: (f64, f64), color: Color) {
+        self.features.push(Feature::LineString {
+            coords: vec![(from.1, from.0), (to.1, to.0)],
+            stroke: hex_color(color),
+        });
+    }
+}
@@ -0,0 +1,104 @@
+use std::{fs::File, io::BufWriter, io::Write};
+
+use crate::{
+    graph::{BaseEdge, BaseNode, GeoGraph, GeoNode, NodeId},
+    paths::EdgeList,
+};
+
+use super::{Color, VisBuilder};
+
+/// renders paths as Google's Encoded Polyline Algorithm Format instead of GeoJSON, for web
+/// mapping frontends that consume polylines directly.
+pub struct PolylineBuilder<'a, N, E> {
+    polylines: Vec<String>,
+    graph: &'a dyn GeoGraph<Node = N, Edge = E>,
+}
+
+impl<'a, N: GeoNode + BaseNode, E: BaseEdge> PolylineBuilder<'a, N, E> {
+    pub fn new(graph: &'a dyn GeoGraph<Node = N, Edge = E>) -> Self {
+        Self {
+            polylines: Vec::new(),
+            graph,
+        }
+    }
+
+    pub fn path_with_color(&mut self, path: &EdgeList, _color: Color) {
+        if path.len() == 0 {
+            return;
+        }
+        let mut coords = Vec::with_capacity(path.len() + 1);
+        for edge in path {
+            let start = self.graph.edge(*edge).source();
+            let node = GeoGraph::node(self.graph, start);
+            coords.push((node.lon(), node.lat()));
+        }
+        let last_end = self.graph.edge(*path.last().unwrap()).target();
+        let node = GeoGraph::node(self.graph, last_end);
+        coords.push((node.lon(), node.lat()));
+
+        self.polylines.push(encode_coords(&coords));
+    }
+}
+
+impl<'a, N: GeoNode + BaseNode, E: BaseEdge> VisBuilder for PolylineBuilder<'a, N, E> {
+    /// writes one encoded polyline per path/point/line, newline separated
+    fn save(&mut self, filename: &str) {
+        let file = File::create(filename).expect("error writing file");
+        let mut buf = BufWriter::new(file);
+
+        for polyline in &self.polylines {
+            writeln!(buf, "{}", polyline).expect("error writing file");
+        }
+    }
+
+    fn point_with_color(&mut self, point: NodeId, _color: Color) {
+        let node = GeoGraph::node(self.graph, point);
+        self.polylines.push(encode_coords(&[(node.lon(), node.lat())]));
+    }
+
+    fn path(&mut self, path: &EdgeList) {
+        self.path_with_color(path, Color::RED)
+    }
+
+    fn line_with_color(&mut self, from: (f64, f64), to: (f64, f64), _color: Color) {
+        self.polylines.push(encode_coords(&[(from.1, from.0), (to.1, to.0)]));
+    }
+}
+
+/// encodes a sequence of (lon, lat) coordinates as one polyline string, latitude before
+/// longitude per vertex, delta-coded against the previous point
+fn encode_coords(coords: &[(f64, f64)]) -> String {
+    let mut result = String::new();
+    let mut prev_lat = 0i64;
+    let mut prev_lon = 0i64;
+
+    for &(lon, lat) in coords {
+        let lat = (lat * 1e5).round() as i64;
+        let lon = (lon * 1e5).round() as i64;
+
+        result.push_str(&encode_value(lat - prev_lat));
+        result.push_str(&encode_value(lon - prev_lon));
+
+        prev_lat = lat;
+        prev_lon = lon;
+    }
+    result
+}
+
+/// encodes a single signed delta per the polyline algorithm: left-shift by one bit, invert all
+/// bits if the original value was negative, then emit 5-bit groups from least significant,
+/// OR-ing every group but the last with 0x20 and offsetting by 63 into printable ASCII
+fn encode_value(value: i64) -> String {
+    let mut value = value << 1;
+    if value < 0 {
+        value = !value;
+    }
+
+    let mut out = String::new();
+    while value >= 0x20 {
+        out.push((((value & 0x1f) | 0x20) + 63) as u8 as char);
+        value >>= 5;
+    }
+    out.push((value + 63) as u8 as char);
+    out
+}
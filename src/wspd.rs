@@ -1,10 +1,79 @@
 use rayon::iter::{ParallelBridge, ParallelIterator};
 use rustc_hash::FxHashSet as Set;
-use std::{cmp::Ordering, hash::Hash, ops::Deref};
+use std::{
+    cmp::Ordering,
+    hash::Hash,
+    ops::{ControlFlow, Deref},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering},
+    time::{Duration, Instant},
+};
 
 // define reference pair of type T
 type Pair<'a, T> = (&'a T, &'a T);
 
+/// rate-limits a progress callback to roughly once per `interval`, electing exactly one winner
+/// among concurrent callers via compare-exchange so it's safe to poll from many rayon workers.
+pub struct ProgressGate {
+    start: Instant,
+    interval_millis: u64,
+    next_millis: AtomicU64,
+}
+
+impl ProgressGate {
+    pub fn new(interval: Duration) -> Self {
+        let interval_millis = interval.as_millis() as u64;
+        Self {
+            start: Instant::now(),
+            interval_millis,
+            next_millis: AtomicU64::new(interval_millis),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// true at most once per `interval`; concurrent callers racing on the same tick all see
+    /// false except the single one that wins the compare-exchange
+    pub fn ready(&self) -> bool {
+        let elapsed_millis = self.elapsed().as_millis() as u64;
+        let next = self.next_millis.load(AtomicOrdering::Relaxed);
+        if elapsed_millis < next {
+            return false;
+        }
+        self.next_millis
+            .compare_exchange(
+                next,
+                elapsed_millis + self.interval_millis,
+                AtomicOrdering::Relaxed,
+                AtomicOrdering::Relaxed,
+            )
+            .is_ok()
+    }
+}
+
+/// snapshot of an in-flight WSPD decomposition, reported to a progress callback on a timer so
+/// long runs can be observed (and aborted) from a UI or server instead of run fire-and-forget.
+#[derive(Debug, Clone, Copy)]
+pub struct WspdProgress {
+    pub pairs_decomposed: usize,
+    pub paths_emitted: usize,
+    pub depth: usize,
+    pub elapsed: Duration,
+}
+
+impl WspdProgress {
+    /// pairs decomposed per second so far
+    pub fn pairs_per_sec(&self) -> f64 {
+        self.pairs_decomposed as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// paths emitted per second so far
+    pub fn paths_per_sec(&self) -> f64 {
+        self.paths_emitted as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
 // wspd is defined based on this tree trait
 pub trait Tree<'a>
 where
@@ -24,15 +93,73 @@ pub struct WSPD<'a, T> {
     pairs: Set<Pair<'a, T>>,
 }
 
+/// shared state for a `new_with_progress` run: a counter of decomposed pairs, a gate deciding
+/// when the next callback invocation is due, and a flag set once the callback asks to stop.
+struct Progress<'a> {
+    gate: ProgressGate,
+    pairs_decomposed: AtomicUsize,
+    aborted: std::sync::atomic::AtomicBool,
+    callback: &'a (dyn Fn(&WspdProgress) -> ControlFlow<()> + Sync),
+}
+
+impl<'a> Progress<'a> {
+    fn is_aborted(&self) -> bool {
+        self.aborted.load(AtomicOrdering::Relaxed)
+    }
+
+    /// records one more decomposed pair found at `depth`, firing the callback if due
+    fn record(&self, depth: usize) {
+        let pairs_decomposed = self.pairs_decomposed.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+        if self.is_aborted() || !self.gate.ready() {
+            return;
+        }
+        let progress = WspdProgress {
+            pairs_decomposed,
+            paths_emitted: 0,
+            depth,
+            elapsed: self.gate.elapsed(),
+        };
+        if (self.callback)(&progress).is_break() {
+            self.aborted.store(true, AtomicOrdering::Relaxed);
+        }
+    }
+}
+
 impl<'a, T: Tree<'a> + Distance + Eq + Hash + Sync> WSPD<'a, T> {
     pub fn new(tree: &'a T, e: f64) -> Self {
         Self {
-            pairs: Self::alg_wspd(&tree, &tree, e),
+            pairs: Self::alg_wspd(&tree, &tree, e, 0, None),
+        }
+    }
+
+    /// like `new`, but invokes `callback` roughly every `interval` with a `WspdProgress`
+    /// snapshot. returning `ControlFlow::Break` stops the decomposition early; whatever pairs
+    /// had already been found up to that point are returned.
+    pub fn new_with_progress(
+        tree: &'a T,
+        e: f64,
+        interval: Duration,
+        callback: impl Fn(&WspdProgress) -> ControlFlow<()> + Sync,
+    ) -> Self {
+        let progress = Progress {
+            gate: ProgressGate::new(interval),
+            pairs_decomposed: AtomicUsize::new(0),
+            aborted: std::sync::atomic::AtomicBool::new(false),
+            callback: &callback,
+        };
+        Self {
+            pairs: Self::alg_wspd(&tree, &tree, e, 0, Some(&progress)),
         }
     }
 
     /// from har-peled book
-    fn alg_wspd(u: &'a T, v: &'a T, e: f64) -> Set<Pair<'a, T>> {
+    fn alg_wspd(u: &'a T, v: &'a T, e: f64, depth: usize, progress: Option<&Progress<'_>>) -> Set<Pair<'a, T>> {
+        if let Some(p) = progress {
+            if p.is_aborted() {
+                return Set::default();
+            }
+        }
+
         if u == v && u.diameter() == 0.0 {
             return Set::default();
         }
@@ -44,6 +171,9 @@ impl<'a, T: Tree<'a> + Distance + Eq + Hash + Sync> WSPD<'a, T> {
         };
 
         if u.diameter() <= e * u.distance(v) {
+            if let Some(p) = progress {
+                p.record(depth);
+            }
             let mut s = Set::default();
             s.insert((u, v));
             return s;
@@ -53,7 +183,7 @@ impl<'a, T: Tree<'a> + Distance + Eq + Hash + Sync> WSPD<'a, T> {
         let res: Set<Pair<'a, T>> = u
             .children()
             .par_bridge()
-            .flat_map(|child| Self::alg_wspd(child, v, e))
+            .flat_map(|child| Self::alg_wspd(child, v, e, depth + 1, progress))
             .collect();
 
         res